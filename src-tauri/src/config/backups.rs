@@ -0,0 +1,149 @@
+use std::{
+    cmp::Reverse,
+    fs,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::info;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{parse_config, ConfigError, ConfigErrorKind};
+use crate::paths::{get_config_backup_path, get_config_backups_directory};
+
+const BACKUP_PREFIX: &str = "config-version-";
+const KEEP_LAST: usize = 5;
+const MAX_AGE: Duration = Duration::from_secs(180 * 86400);
+
+/// A backup of the config file taken before an update or migration
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBackup {
+    pub version: u32,
+    pub created_at: i64,
+    pub size: u64,
+    pub path: String,
+}
+
+/// An overview of the contents of a config backup
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBackupSummary {
+    pub version: u32,
+    pub config_version: Option<u64>,
+    pub previous_version: Option<String>,
+    pub connections: usize,
+    pub connection_names: Vec<String>,
+    pub query_tabs: usize,
+    pub history_entries: usize,
+    pub saved_queries: usize,
+}
+
+/// Parse the config version from a backup file name
+fn parse_version(name: &str) -> Option<u32> {
+    name.strip_prefix(BACKUP_PREFIX)?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
+
+/// Read all config backups, newest first
+fn read_backups() -> Vec<ConfigBackup> {
+    let Ok(entries) = fs::read_dir(get_config_backups_directory()) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<ConfigBackup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = parse_version(entry.file_name().to_str()?)?;
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+
+            Some(ConfigBackup {
+                version,
+                created_at: modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64,
+                size: metadata.len(),
+                path: entry.path().to_string_lossy().into_owned(),
+            })
+        })
+        .collect();
+
+    backups.sort_by_key(|b| Reverse(b.created_at));
+    backups
+}
+
+/// Count the entries of an array field on every connection
+fn count_per_connection(connections: &[Value], field: &str) -> usize {
+    connections
+        .iter()
+        .filter_map(|c| c.get(field).and_then(Value::as_array))
+        .map(Vec::len)
+        .sum()
+}
+
+///
+/// Delete backups no longer covered by the retention rules. The most recent
+/// backups are always kept, older ones only until they expire.
+///
+pub fn apply_retention() {
+    let expiry = SystemTime::now()
+        .checked_sub(MAX_AGE)
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as i64);
+
+    for backup in read_backups().iter().skip(KEEP_LAST) {
+        if backup.created_at < expiry {
+            info!("Pruning config backup for version {}", backup.version);
+
+            let _ = fs::remove_file(&backup.path);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_config_backups() -> Vec<ConfigBackup> {
+    read_backups()
+}
+
+#[tauri::command]
+pub fn preview_config_backup(version: u32) -> Result<ConfigBackupSummary, ConfigError> {
+    let contents = fs::read_to_string(get_config_backup_path(version))
+        .map_err(|e| ConfigError::io("read backup", e))?;
+
+    let config =
+        parse_config(&contents).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    let connections = config
+        .get("connections")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    Ok(ConfigBackupSummary {
+        version,
+        config_version: config.get("configVersion").and_then(Value::as_u64),
+        previous_version: config
+            .get("previousVersion")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        connections: connections.len(),
+        connection_names: connections
+            .iter()
+            .filter_map(|c| c.get("name").and_then(Value::as_str))
+            .map(str::to_owned)
+            .collect(),
+        query_tabs: count_per_connection(connections, "queries"),
+        history_entries: count_per_connection(connections, "queryHistory"),
+        saved_queries: config
+            .get("savedQueries")
+            .and_then(Value::as_array)
+            .map_or(0, Vec::len),
+    })
+}
+
+#[tauri::command]
+pub fn delete_config_backup(version: u32) -> Result<(), ConfigError> {
+    fs::remove_file(get_config_backup_path(version))
+        .map_err(|e| ConfigError::io("delete backup", e))
+}
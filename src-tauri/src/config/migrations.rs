@@ -0,0 +1,323 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{AppHandle, State, Window};
+
+use super::{
+    backups::apply_retention,
+    patch::{apply_operation, PatchOperation},
+    stores::{commit_stores, current_document},
+    sync::ConfigSyncState,
+    write_config, ConfigError,
+};
+use crate::paths::get_config_backup_path;
+
+/// The config version written by this version of Surrealist. Must be kept
+/// in sync with `CONFIG_VERSION` in `src/util/defaults.tsx`.
+pub const CONFIG_VERSION: u64 = 2;
+
+/// The migration steps shared with the frontend, see `applyMigrations`
+const MIGRATIONS: &str = include_str!("../../../src/assets/data/config-migrations.json");
+
+/// A breaking change between two consecutive config versions. Each step is
+/// a list of JSON Patches applied to every connection. A patch is skipped
+/// when a tested value does not match or a moved or copied value does not
+/// exist, while other failures are logged.
+#[derive(Deserialize)]
+struct Migration {
+    from: u64,
+    upgrade: Vec<Vec<PatchOperation>>,
+    downgrade: Option<Vec<Vec<PatchOperation>>>,
+}
+
+/// The result of migrating the config file
+#[derive(Serialize)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum MigrationReport {
+    Current { version: u64 },
+    Upgraded { from: u64, to: u64, backup: String },
+    Downgraded { from: u64, to: u64, backup: String },
+    Refused { version: u64, reason: String },
+}
+
+/// All migrations, ordered by the version they upgrade from
+fn migrations() -> Vec<Migration> {
+    serde_json::from_str(MIGRATIONS).expect("config migrations should be valid")
+}
+
+/// Returns whether the precondition of an operation holds, meaning a tested
+/// value matches and a moved or copied value exists
+fn is_applicable(document: &Value, operation: &PatchOperation) -> bool {
+    match operation {
+        PatchOperation::Test { path, value } => document.pointer(path) == Some(value),
+        PatchOperation::Move { from, .. } | PatchOperation::Copy { from, .. } => {
+            document.pointer(from).is_some()
+        }
+        _ => true,
+    }
+}
+
+/// Apply a patch to a copy of a connection, or nothing when it does not apply
+fn apply_patch(
+    connection: &Map<String, Value>,
+    patch: &[PatchOperation],
+) -> Result<Option<Map<String, Value>>, String> {
+    let mut patched = Value::Object(connection.clone());
+
+    for operation in patch {
+        if !is_applicable(&patched, operation) {
+            return Ok(None);
+        }
+
+        apply_operation(&mut patched, operation.clone())?;
+    }
+
+    match patched {
+        Value::Object(patched) => Ok(Some(patched)),
+        _ => Err("Connection is no longer an object".into()),
+    }
+}
+
+/// Apply a step to every connection, including the sandbox
+fn apply_to_connections(config: &mut Map<String, Value>, step: &[Vec<PatchOperation>]) {
+    let apply = |connection: &mut Map<String, Value>| {
+        for patch in step {
+            match apply_patch(connection, patch) {
+                Ok(Some(patched)) => *connection = patched,
+                Ok(None) => {}
+                Err(err) => warn!(
+                    "Failed to apply config migration patch {}: {}",
+                    serde_json::to_string(patch).unwrap_or_default(),
+                    err
+                ),
+            }
+        }
+    };
+
+    if let Some(Value::Array(connections)) = config.get_mut("connections") {
+        for connection in connections.iter_mut().filter_map(Value::as_object_mut) {
+            apply(connection);
+        }
+    }
+
+    if let Some(Value::Object(sandbox)) = config.get_mut("sandbox") {
+        apply(sandbox);
+    }
+}
+
+/// Validate the shape of a migrated config
+fn validate(config: &Map<String, Value>, version: u64) -> Result<(), String> {
+    if config.get("configVersion").and_then(Value::as_u64) != Some(version) {
+        return Err("Migrated config has an unexpected version".into());
+    }
+
+    if config.get("settings").is_some_and(|s| !s.is_object()) {
+        return Err("Migrated config has invalid settings".into());
+    }
+
+    match config.get("connections") {
+        None => Ok(()),
+        Some(Value::Array(connections)) if connections.iter().all(Value::is_object) => Ok(()),
+        Some(_) => Err("Migrated config has invalid connections".into()),
+    }
+}
+
+/// Run the migration steps between two versions in order
+pub(super) fn run_steps(config: &mut Map<String, Value>, from: u64, to: u64) -> Result<(), String> {
+    let migrations = migrations();
+    let mut version = from;
+
+    while version != to {
+        if version < to {
+            let step = migrations
+                .iter()
+                .find(|m| m.from == version)
+                .ok_or_else(|| format!("No migration from version {}", version))?;
+
+            apply_to_connections(config, &step.upgrade);
+            version += 1;
+        } else {
+            let step = migrations
+                .iter()
+                .find(|m| m.from == version - 1)
+                .and_then(|m| m.downgrade.as_ref())
+                .ok_or_else(|| format!("Version {} cannot be downgraded", version))?;
+
+            apply_to_connections(config, step);
+            version -= 1;
+        }
+
+        config.insert("configVersion".into(), json!(version));
+    }
+
+    Ok(())
+}
+
+///
+/// Bring the config to the given version, backing up the original first,
+/// and notify all windows. Newer configs are only downgraded when
+/// explicitly allowed.
+///
+#[tauri::command]
+pub fn migrate_config(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    target_version: Option<u64>,
+    allow_downgrade: Option<bool>,
+) -> Result<MigrationReport, ConfigError> {
+    let target = target_version.unwrap_or(CONFIG_VERSION);
+    let mut current = state.0.lock().unwrap();
+    let mut config = current_document(&current)?;
+
+    // Configs without a version are fresh and populated by the frontend
+    let Some(version) = config.get("configVersion").and_then(Value::as_u64) else {
+        return Ok(MigrationReport::Current { version: target });
+    };
+
+    if version == target {
+        return Ok(MigrationReport::Current { version });
+    }
+
+    if version > target && !allow_downgrade.unwrap_or_default() {
+        return Ok(MigrationReport::Refused {
+            version,
+            reason: format!(
+                "Config version {} is newer than the supported version {}",
+                version, target
+            ),
+        });
+    }
+
+    let original = Value::Object(config.clone()).to_string();
+
+    if let Err(reason) =
+        run_steps(&mut config, version, target).and_then(|_| validate(&config, target))
+    {
+        return Ok(MigrationReport::Refused { version, reason });
+    }
+
+    let backup = get_config_backup_path(version as u32);
+
+    write_config(&original, backup.clone())?;
+    commit_stores(&app, &window, &mut current, config)?;
+    apply_retention();
+
+    info!("Migrated config from version {} to {}", version, target);
+
+    let backup = backup.to_string_lossy().into_owned();
+
+    Ok(if version < target {
+        MigrationReport::Upgraded {
+            from: version,
+            to: target,
+            backup,
+        }
+    } else {
+        MigrationReport::Downgraded {
+            from: version,
+            to: target,
+            backup,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(connection: Value) -> Map<String, Value> {
+        let Value::Object(config) = json!({ "configVersion": 1, "connections": [connection] })
+        else {
+            unreachable!()
+        };
+
+        config
+    }
+
+    #[test]
+    fn upgrades_root_connection() {
+        let mut config = config(json!({
+            "id": "a",
+            "connection": {
+                "authMode": "root",
+                "namespace": "ns",
+                "database": "db",
+                "scopeFields": null
+            }
+        }));
+
+        run_steps(&mut config, 1, 2).unwrap();
+
+        assert_eq!(
+            config["connections"][0],
+            json!({
+                "id": "a",
+                "authentication": {
+                    "mode": "root",
+                    "namespace": "",
+                    "database": "",
+                    "scopeFields": null,
+                    "accessFields": []
+                },
+                "lastNamespace": "ns",
+                "lastDatabase": "db",
+                "graphqlQuery": "",
+                "graphqlVariables": ""
+            })
+        );
+        assert_eq!(config["configVersion"], json!(2));
+    }
+
+    #[test]
+    fn upgrades_scope_connection() {
+        let fields = json!([{ "subject": "user", "value": "tobie" }]);
+        let mut config = config(json!({
+            "connection": {
+                "authMode": "scope",
+                "namespace": "ns",
+                "database": "db",
+                "scopeFields": fields
+            }
+        }));
+
+        run_steps(&mut config, 1, 2).unwrap();
+
+        let authentication = &config["connections"][0]["authentication"];
+
+        assert_eq!(authentication["scopeFields"], fields);
+        assert_eq!(authentication["accessFields"], fields);
+        assert_eq!(authentication["namespace"], json!("ns"));
+    }
+
+    #[test]
+    fn downgrades_connection() {
+        let mut config = config(json!({
+            "authentication": { "mode": "namespace", "namespace": "", "accessFields": [] },
+            "lastNamespace": "ns",
+            "lastDatabase": null,
+            "graphqlQuery": "",
+            "graphqlVariables": ""
+        }));
+
+        run_steps(&mut config, 2, 1).unwrap();
+
+        assert_eq!(
+            config["connections"][0],
+            json!({
+                "connection": { "authMode": "namespace", "namespace": "ns", "scopeFields": [] }
+            })
+        );
+    }
+
+    #[test]
+    fn skips_failing_patch() {
+        let connection = Map::new();
+        let patch = vec![PatchOperation::Remove {
+            path: "/missing".into(),
+        }];
+
+        assert!(apply_patch(&connection, &patch).is_err());
+        assert_eq!(apply_patch(&connection, &[]).unwrap(), Some(Map::new()));
+    }
+}
@@ -0,0 +1,480 @@
+use std::{
+    fs::{self, File},
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use log::warn;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, State, Window};
+use time::OffsetDateTime;
+
+pub mod backups;
+pub mod migrations;
+pub mod patch;
+pub mod stores;
+pub mod sync;
+pub mod vault;
+
+use self::sync::{ConfigChanged, ConfigSyncState, Fingerprint};
+use crate::paths::{get_config_backup_path, get_config_backups_directory, get_config_path};
+
+const DEFAULT_CONFIG: &str = "{}";
+
+/// OS error codes reported when the disk is full
+#[cfg(windows)]
+const DISK_FULL_ERRORS: [i32; 2] = [39, 112];
+#[cfg(not(windows))]
+const DISK_FULL_ERRORS: [i32; 1] = [28];
+
+static NEXT_TEMP_ID: AtomicU32 = AtomicU32::new(0);
+
+/// The reason a config command failed
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigErrorKind {
+    InvalidJson,
+    NotFound,
+    PermissionDenied,
+    DiskFull,
+    Conflict,
+    InvalidPassword,
+    KeyringUnavailable,
+    VaultLocked,
+    Io,
+}
+
+/// An error returned by the config commands
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigError {
+    pub kind: ConfigErrorKind,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(kind: ConfigErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Describe a failed config file operation in a way suitable for the user
+    pub fn io(action: &str, err: io::Error) -> Self {
+        if err
+            .raw_os_error()
+            .is_some_and(|code| DISK_FULL_ERRORS.contains(&code))
+        {
+            return Self::new(
+                ConfigErrorKind::DiskFull,
+                format!("Failed to {} config: the disk is full", action),
+            );
+        }
+
+        match err.kind() {
+            ErrorKind::PermissionDenied => Self::new(
+                ConfigErrorKind::PermissionDenied,
+                format!("Failed to {} config: permission denied", action),
+            ),
+            ErrorKind::NotFound => Self::new(
+                ConfigErrorKind::NotFound,
+                format!("Failed to {} config: file not found", action),
+            ),
+            _ => Self::new(
+                ConfigErrorKind::Io,
+                format!("Failed to {} config: {}", action, err),
+            ),
+        }
+    }
+}
+
+/// Where a corrupted config was recovered from
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecoverySource {
+    Previous,
+    Backup,
+    Default,
+}
+
+/// Describes the recovery of a corrupted config file
+#[derive(Serialize)]
+pub struct ConfigRecovery {
+    pub reason: String,
+    pub quarantined: String,
+    pub source: RecoverySource,
+    pub path: Option<String>,
+}
+
+/// The loaded config, its revision and any recovery that took place
+#[derive(Serialize)]
+pub struct LoadedConfig {
+    pub config: String,
+    pub recovery: Option<ConfigRecovery>,
+    pub revision: u64,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A file next to the given path with an additional extension
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+
+    name.push(".");
+    name.push(extension);
+
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn sync_directory(path: &Path) {
+    let _ = File::open(path).and_then(|dir| dir.sync_all());
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) {}
+
+///
+/// Keep the current contents of a file as its `.previous` sibling. The link
+/// is created under a temporary name first and renamed, so an existing
+/// `.previous` is replaced atomically. File systems without hard links fall
+/// back to a copy.
+///
+fn keep_previous(path: &Path, temp: &Path) -> io::Result<()> {
+    let link = sibling_path(temp, "previous");
+
+    if fs::hard_link(path, &link).is_err() {
+        fs::copy(path, &link)?;
+    }
+
+    fs::rename(&link, sibling_path(path, "previous")).inspect_err(|_| {
+        let _ = fs::remove_file(&link);
+    })
+}
+
+///
+/// Write a file by writing to a temporary sibling, flushing it to disk and
+/// renaming it over the target. The replaced file is kept as `.previous`
+/// through a hard link, so it is not copied. Each write uses its own
+/// temporary file, so concurrent writers of the same path never write into
+/// each other's file.
+///
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let parent = path.parent().ok_or(ErrorKind::InvalidInput)?;
+    let temp = sibling_path(
+        path,
+        &format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+        ),
+    );
+
+    fs::create_dir_all(parent)?;
+
+    let result = (|| {
+        let mut file = File::create_new(&temp)?;
+
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        if path.exists() {
+            keep_previous(path, &temp)?;
+        }
+
+        fs::rename(&temp, path)?;
+        sync_directory(parent);
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+
+    result
+}
+
+/// Write a config document as pretty printed JSON
+fn write_document(document: &Map<String, Value>, path: &Path) -> Result<(), ConfigError> {
+    let mut pretty_config = serde_json::to_string_pretty(document)
+        .map_err(|e| ConfigError::new(ConfigErrorKind::Io, e.to_string()))?;
+
+    pretty_config.push('\n');
+
+    write_atomic(path, pretty_config.as_bytes()).map_err(|e| ConfigError::io("write", e))
+}
+
+fn write_config(config: &str, path: PathBuf) -> Result<(), ConfigError> {
+    let mut document =
+        parse_config(config).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    vault::seal_document(&mut document, false)?;
+
+    write_document(&document, &path)
+}
+
+/// Parse config contents, requiring a JSON object
+fn parse_config(contents: &str) -> Result<Map<String, Value>, String> {
+    match serde_json::from_str(contents).map_err(|e| e.to_string())? {
+        Value::Object(config) => Ok(config),
+        _ => Err("Config is not a JSON object".into()),
+    }
+}
+
+/// The paths of all version backups
+fn config_backup_paths() -> impl Iterator<Item = PathBuf> {
+    fs::read_dir(get_config_backups_directory())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+}
+
+/// The most recently written valid config among the given files
+fn newest_valid_config(
+    candidates: impl Iterator<Item = (RecoverySource, PathBuf)>,
+) -> Option<(RecoverySource, PathBuf, String)> {
+    candidates
+        .filter_map(|(source, path)| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            let contents = fs::read_to_string(&path).ok()?;
+
+            parse_config(&contents).ok()?;
+
+            Some((modified, source, path, contents))
+        })
+        .max_by_key(|(modified, ..)| *modified)
+        .map(|(_, source, path, contents)| (source, path, contents))
+}
+
+/// Find the most recently written valid copy of the config, considering the
+/// last known good copy and the version backups
+fn find_recovery_source() -> Option<(RecoverySource, PathBuf, String)> {
+    let previous = sibling_path(&get_config_path(), "previous");
+    let backups = config_backup_paths().map(|path| (RecoverySource::Backup, path));
+
+    newest_valid_config(std::iter::once((RecoverySource::Previous, previous)).chain(backups))
+}
+
+///
+/// Move a corrupted config file aside and replace it with the most recent
+/// valid copy, or the default config when none exists
+///
+fn recover_config(reason: String) -> Result<LoadedConfig, ConfigError> {
+    let config_path = get_config_path();
+    let quarantined = config_path.with_file_name(format!(
+        "config.corrupt-{}.json",
+        OffsetDateTime::now_utc().unix_timestamp()
+    ));
+
+    fs::rename(&config_path, &quarantined).map_err(|e| ConfigError::io("quarantine", e))?;
+
+    let (source, path, contents) = find_recovery_source().unwrap_or((
+        RecoverySource::Default,
+        PathBuf::new(),
+        DEFAULT_CONFIG.to_owned(),
+    ));
+
+    warn!(
+        "Config was corrupted ({}), moved to {} and recovered from {:?}",
+        reason,
+        quarantined.display(),
+        source
+    );
+
+    write_atomic(&config_path, contents.as_bytes()).map_err(|e| ConfigError::io("recover", e))?;
+
+    Ok(LoadedConfig {
+        config: contents,
+        recovery: Some(ConfigRecovery {
+            reason,
+            quarantined: quarantined.to_string_lossy().into_owned(),
+            source,
+            path: (source != RecoverySource::Default).then(|| path.to_string_lossy().into_owned()),
+        }),
+        revision: 0,
+    })
+}
+
+/// Read the legacy config file, recovering it when corrupted
+fn read_legacy_config() -> Result<LoadedConfig, ConfigError> {
+    let config_path = get_config_path();
+
+    // Attempt to read the config file
+    let read_op = File::open(config_path);
+    let mut buffer = String::new();
+
+    match read_op {
+        Ok(mut file) => {
+            file.read_to_string(&mut buffer)
+                .map_err(|e| ConfigError::io("read", e))?;
+
+            if let Err(reason) = parse_config(&buffer) {
+                return recover_config(reason);
+            }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            if let Err(err) = write_config(DEFAULT_CONFIG, get_config_path()) {
+                warn!("{}", err);
+            }
+
+            buffer = DEFAULT_CONFIG.to_string();
+        }
+        Err(err) => return Err(ConfigError::io("read", err)),
+    }
+
+    Ok(LoadedConfig {
+        config: buffer,
+        recovery: None,
+        revision: 0,
+    })
+}
+
+/// Read the config from its stores, splitting up the legacy config file first
+fn read_config() -> Result<LoadedConfig, ConfigError> {
+    let migrated = stores::migrate_legacy_config()?;
+    let loaded = stores::read_stores(true)?;
+
+    Ok(LoadedConfig {
+        recovery: migrated.or(loaded.recovery),
+        ..loaded
+    })
+}
+
+#[tauri::command]
+pub fn load_config(state: State<ConfigSyncState>) -> Result<LoadedConfig, ConfigError> {
+    let mut current = state.0.lock().unwrap();
+
+    // Changes which were not written yet take precedence over the file
+    if let Some(pending) = &current.pending {
+        return Ok(LoadedConfig {
+            config: Value::Object(pending.clone()).to_string(),
+            recovery: None,
+            revision: current.revision,
+        });
+    }
+
+    let loaded = read_config()?;
+
+    current.fingerprint = Fingerprint::current();
+
+    Ok(LoadedConfig {
+        revision: current.revision,
+        ..loaded
+    })
+}
+
+///
+/// Write the config file and notify all windows. When a revision is given,
+/// the write is rejected if the config changed since that revision.
+///
+#[tauri::command]
+pub fn save_config(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    config: &str,
+    revision: Option<u64>,
+) -> Result<u64, ConfigError> {
+    let mut current = state.0.lock().unwrap();
+
+    current.check_revision(revision)?;
+
+    let document =
+        parse_config(config).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    stores::write_stores(&document)?;
+
+    current.pending = None;
+    current.dirty.clear();
+
+    let revision = current.advance();
+
+    let _ = app.emit(
+        "config:changed",
+        ConfigChanged {
+            revision,
+            config: Some(config.to_owned()),
+            patch: None,
+            source: Some(window.label().to_owned()),
+        },
+    );
+
+    Ok(revision)
+}
+
+#[tauri::command]
+pub fn backup_config(config: &str, version: u32) -> Result<(), ConfigError> {
+    write_config(config, get_config_backup_path(version))?;
+    backups::apply_retention();
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn has_config_backup(version: u32) -> bool {
+    get_config_backup_path(version).exists()
+}
+
+///
+/// Replace the config with a version backup and notify all windows
+///
+#[tauri::command]
+pub fn restore_config_backup(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    version: u32,
+) -> Result<u64, ConfigError> {
+    let backup_path = get_config_backup_path(version);
+
+    if !backup_path.exists() {
+        return Err(ConfigError::new(
+            ConfigErrorKind::NotFound,
+            "Backup does not exist",
+        ));
+    }
+
+    let backup = fs::read_to_string(backup_path).map_err(|e| ConfigError::io("read backup", e))?;
+    let document =
+        parse_config(&backup).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    // Unwritten changes are discarded so they do not overwrite the backup
+    let mut current = state.0.lock().unwrap();
+
+    stores::commit_stores(&app, &window, &mut current, document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_replaced_file_as_previous() {
+        let directory =
+            std::env::temp_dir().join(format!("surrealist-atomic-{}", std::process::id()));
+        let path = directory.join("config.json");
+        let previous = sibling_path(&path, "previous");
+
+        let _ = fs::remove_dir_all(&directory);
+
+        write_atomic(&path, b"first").unwrap();
+        assert!(!previous.exists());
+
+        write_atomic(&path, b"second").unwrap();
+        write_atomic(&path, b"third").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"third");
+        assert_eq!(fs::read(&previous).unwrap(), b"second");
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use super::{
+    stores::{current_document, ConfigStore},
+    sync::{schedule_flush, ConfigChanged, ConfigRevision, ConfigSyncState},
+    vault::check_unlocked,
+    ConfigError, ConfigErrorKind,
+};
+
+/// A single JSON Patch (RFC 6902) operation on the config
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+impl PatchOperation {
+    fn path(&self) -> &str {
+        match self {
+            Self::Add { path, .. }
+            | Self::Remove { path }
+            | Self::Replace { path, .. }
+            | Self::Move { path, .. }
+            | Self::Copy { path, .. }
+            | Self::Test { path, .. } => path,
+        }
+    }
+}
+
+/// Split a JSON pointer into its unescaped reference tokens
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tokens = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| format!("Invalid path {}", pointer))?;
+
+    Ok(tokens
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Read the value at a pointer, which must exist
+fn read_pointer(document: &Value, pointer: &str) -> Result<Value, String> {
+    parse_pointer(pointer)?;
+
+    document
+        .pointer(pointer)
+        .cloned()
+        .ok_or_else(|| format!("Path {} does not exist", pointer))
+}
+
+/// Apply a single operation to a document. Moves and copies are applied as
+/// the removal and addition they consist of.
+pub fn apply_operation(document: &mut Value, operation: PatchOperation) -> Result<(), String> {
+    let operation = match operation {
+        PatchOperation::Test { path, value } => {
+            return match read_pointer(document, &path)? == value {
+                true => Ok(()),
+                false => Err(format!("Test of {} failed", path)),
+            };
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = read_pointer(document, &from)?;
+
+            PatchOperation::Add { path, value }
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(format!("Cannot move {} into itself", from));
+            }
+
+            let value = read_pointer(document, &from)?;
+
+            apply_operation(document, PatchOperation::Remove { path: from })?;
+
+            PatchOperation::Add { path, value }
+        }
+        operation => operation,
+    };
+
+    let path = operation.path().to_owned();
+    let missing = || format!("Path {} does not exist", path);
+    let mut tokens = parse_pointer(&path)?;
+
+    let Some(last) = tokens.pop() else {
+        return match operation {
+            PatchOperation::Add { value, .. } | PatchOperation::Replace { value, .. } => {
+                *document = value;
+                Ok(())
+            }
+            _ => Err("Cannot remove the config itself".into()),
+        };
+    };
+
+    let parent = tokens
+        .iter()
+        .try_fold(document, |node, token| match node {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(list) => token.parse().ok().and_then(|i: usize| list.get_mut(i)),
+            _ => None,
+        })
+        .ok_or_else(missing)?;
+
+    match (parent, operation) {
+        (Value::Object(map), PatchOperation::Remove { .. }) => {
+            map.remove(&last).map(drop).ok_or_else(missing)
+        }
+        (Value::Object(map), PatchOperation::Replace { value, .. }) => {
+            let entry = map.get_mut(&last).ok_or_else(missing)?;
+
+            *entry = value;
+            Ok(())
+        }
+        (Value::Object(map), PatchOperation::Add { value, .. }) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        (Value::Array(list), PatchOperation::Add { value, .. }) if last == "-" => {
+            list.push(value);
+            Ok(())
+        }
+        (Value::Array(list), operation) => {
+            let index: usize = last.parse().map_err(|_| missing())?;
+
+            match operation {
+                PatchOperation::Add { value, .. } if index <= list.len() => {
+                    list.insert(index, value);
+                    Ok(())
+                }
+                PatchOperation::Replace { value, .. } if index < list.len() => {
+                    list[index] = value;
+                    Ok(())
+                }
+                PatchOperation::Remove { .. } if index < list.len() => {
+                    list.remove(index);
+                    Ok(())
+                }
+                _ => Err(missing()),
+            }
+        }
+        _ => Err(missing()),
+    }
+}
+
+/// Apply a patch to the pending config, schedule it to be written and notify
+/// all windows
+fn apply_patch(
+    app: &AppHandle,
+    current: &mut ConfigRevision,
+    patch: Vec<PatchOperation>,
+    source: Option<String>,
+) -> Result<u64, ConfigError> {
+    let document = current_document(current)?;
+
+    // Patches are applied to a copy so a failing operation leaves the
+    // config untouched
+    let mut patched = Value::Object(document);
+
+    patch
+        .iter()
+        .cloned()
+        .try_for_each(|op| apply_operation(&mut patched, op))
+        .map_err(|e| {
+            ConfigError::new(
+                ConfigErrorKind::InvalidJson,
+                format!("Failed to apply config patch: {}", e),
+            )
+        })?;
+
+    let Value::Object(mut patched) = patched else {
+        return Err(ConfigError::new(
+            ConfigErrorKind::InvalidJson,
+            "Config is not a JSON object",
+        ));
+    };
+
+    // Secrets entered while the vault is locked could not be written later
+    check_unlocked(&mut patched)?;
+
+    for operation in &patch {
+        current
+            .dirty
+            .extend(ConfigStore::affected_by(operation.path()));
+
+        if let PatchOperation::Move { from, .. } = operation {
+            current.dirty.extend(ConfigStore::affected_by(from));
+        }
+    }
+
+    current.pending = Some(patched);
+    current.revision += 1;
+
+    schedule_flush(app, current);
+
+    let _ = app.emit(
+        "config:changed",
+        ConfigChanged {
+            revision: current.revision,
+            config: None,
+            patch: Some(patch),
+            source,
+        },
+    );
+
+    Ok(current.revision)
+}
+
+///
+/// Apply a patch to the config on behalf of the backend
+///
+pub fn update_config(app: &AppHandle, patch: Vec<PatchOperation>) -> Result<u64, ConfigError> {
+    let state = app.state::<ConfigSyncState>();
+    let mut current = state.0.lock().unwrap();
+
+    apply_patch(app, &mut current, patch, None)
+}
+
+///
+/// Apply a JSON Patch to the config and notify all windows. Changes are
+/// kept in memory and written to disk shortly after, coalescing bursts of
+/// small updates into a single write. When a revision is given, the patch
+/// is rejected if the config changed since that revision.
+///
+#[tauri::command]
+pub fn patch_config(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    patch: Vec<PatchOperation>,
+    revision: Option<u64>,
+) -> Result<u64, ConfigError> {
+    let mut current = state.0.lock().unwrap();
+
+    current.check_revision(revision)?;
+
+    apply_patch(&app, &mut current, patch, Some(window.label().to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn patched(mut document: Value, patch: Value) -> Result<Value, String> {
+        let patch: Vec<PatchOperation> = serde_json::from_value(patch).unwrap();
+
+        patch
+            .into_iter()
+            .try_for_each(|op| apply_operation(&mut document, op))
+            .map(|_| document)
+    }
+
+    #[test]
+    fn applies_object_operations() {
+        let document = json!({ "a": 1, "b": { "c": 2 } });
+        let patch = json!([
+            { "op": "add", "path": "/d", "value": 3 },
+            { "op": "remove", "path": "/a" },
+            { "op": "replace", "path": "/b/c", "value": 4 },
+            { "op": "copy", "from": "/b", "path": "/e" },
+            { "op": "move", "from": "/d", "path": "/b/d" },
+            { "op": "test", "path": "/e/c", "value": 4 }
+        ]);
+
+        assert_eq!(
+            patched(document, patch).unwrap(),
+            json!({ "b": { "c": 4, "d": 3 }, "e": { "c": 4 } })
+        );
+    }
+
+    #[test]
+    fn applies_array_operations() {
+        let document = json!({ "list": [1, 2, 3] });
+        let patch = json!([
+            { "op": "add", "path": "/list/1", "value": 4 },
+            { "op": "add", "path": "/list/-", "value": 5 },
+            { "op": "remove", "path": "/list/0" },
+            { "op": "replace", "path": "/list/2", "value": 6 },
+            { "op": "move", "from": "/list/0", "path": "/list/3" }
+        ]);
+
+        assert_eq!(
+            patched(document, patch).unwrap(),
+            json!({ "list": [2, 6, 5, 4] })
+        );
+    }
+
+    #[test]
+    fn unescapes_pointers() {
+        let document = json!({ "a/b": 1, "c~d": 2 });
+        let patch = json!([
+            { "op": "remove", "path": "/a~1b" },
+            { "op": "replace", "path": "/c~0d", "value": 3 }
+        ]);
+
+        assert_eq!(patched(document, patch).unwrap(), json!({ "c~d": 3 }));
+    }
+
+    #[test]
+    fn rejects_invalid_operations() {
+        let document = json!({ "a": { "b": 1 }, "list": [1] });
+        let invalid = [
+            json!({ "op": "remove", "path": "/missing" }),
+            json!({ "op": "replace", "path": "/missing", "value": 1 }),
+            json!({ "op": "add", "path": "/missing/a", "value": 1 }),
+            json!({ "op": "add", "path": "/list/2", "value": 1 }),
+            json!({ "op": "remove", "path": "/list/1" }),
+            json!({ "op": "remove", "path": "/list/x" }),
+            json!({ "op": "test", "path": "/a/b", "value": 2 }),
+            json!({ "op": "copy", "from": "/missing", "path": "/b" }),
+            json!({ "op": "move", "from": "/a", "path": "/a/c" }),
+            json!({ "op": "remove", "path": "" }),
+            json!({ "op": "add", "path": "a", "value": 1 }),
+        ];
+
+        for operation in invalid {
+            let mut copy = document.clone();
+            let operation: PatchOperation = serde_json::from_value(operation).unwrap();
+
+            assert!(apply_operation(&mut copy, operation).is_err());
+            assert_eq!(copy, document);
+        }
+    }
+
+    #[test]
+    fn replaces_whole_document() {
+        let patch = json!([{ "op": "replace", "path": "", "value": { "a": 1 } }]);
+
+        assert_eq!(patched(json!({}), patch).unwrap(), json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn routes_paths_to_stores() {
+        assert_eq!(ConfigStore::affected_by(""), ConfigStore::ALL.to_vec());
+        assert_eq!(
+            ConfigStore::affected_by("/settings/cloud/urlApiBase"),
+            vec![ConfigStore::Cloud]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/settings/appearance"),
+            vec![ConfigStore::Settings]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/connections/0/queries/1"),
+            vec![ConfigStore::Tabs]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/sandbox/queryHistory"),
+            vec![ConfigStore::History]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/connections/0/name"),
+            vec![ConfigStore::Connections]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/connections/0"),
+            vec![
+                ConfigStore::Connections,
+                ConfigStore::Tabs,
+                ConfigStore::History
+            ]
+        );
+        assert_eq!(
+            ConfigStore::affected_by("/commandHistory/-"),
+            vec![ConfigStore::History]
+        );
+    }
+}
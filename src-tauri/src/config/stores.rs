@@ -0,0 +1,652 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, State, Window};
+use time::OffsetDateTime;
+
+use super::{
+    config_backup_paths,
+    migrations::{run_steps, CONFIG_VERSION},
+    newest_valid_config, parse_config, read_legacy_config, sibling_path,
+    sync::{write_pending, ConfigChanged, ConfigRevision, ConfigSyncState},
+    vault::{reveal_document, seal_document},
+    write_atomic, ConfigError, ConfigErrorKind, ConfigRecovery, LoadedConfig, RecoverySource,
+};
+use crate::paths::{get_config_path, get_config_stores_directory};
+
+const CONTENTS_DIRECTORY: &str = "tabs";
+const CONTENTS_EXTENSION: &str = "surql";
+
+/// A separately persisted part of the config
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigStore {
+    Settings,
+    Connections,
+    Tabs,
+    History,
+    Cloud,
+}
+
+impl ConfigStore {
+    pub const ALL: [Self; 5] = [
+        Self::Settings,
+        Self::Connections,
+        Self::Tabs,
+        Self::History,
+        Self::Cloud,
+    ];
+
+    /// The stores holding the config value at the given JSON pointer
+    pub fn affected_by(pointer: &str) -> Vec<Self> {
+        let mut tokens = pointer.split('/').skip(1);
+        let field = match tokens.next() {
+            None => return Self::ALL.to_vec(),
+            Some("connections") => tokens.nth(1),
+            Some("sandbox") => tokens.next(),
+            Some("commandHistory") => return vec![Self::History],
+            Some("settings") => {
+                return match tokens.next() {
+                    Some("cloud") => vec![Self::Cloud],
+                    Some(_) => vec![Self::Settings],
+                    None => vec![Self::Settings, Self::Cloud],
+                }
+            }
+            Some(_) => return vec![Self::Settings],
+        };
+
+        match field {
+            Some("queries" | "activeQuery") => vec![Self::Tabs],
+            Some("queryHistory") => vec![Self::History],
+            Some("id") | None => vec![Self::Connections, Self::Tabs, Self::History],
+            Some(_) => vec![Self::Connections],
+        }
+    }
+
+    pub fn path(self) -> PathBuf {
+        let name = match self {
+            Self::Settings => "settings.json",
+            Self::Connections => "connections.json",
+            Self::Tabs => "tabs.json",
+            Self::History => "history.json",
+            Self::Cloud => "cloud.json",
+        };
+
+        get_config_stores_directory().join(name)
+    }
+}
+
+/// The config split into its stores. Connections hold their settings and
+/// authentication, while their tabs and history are keyed by connection id.
+/// The queries of tabs are kept as individual files, and the cloud settings
+/// are kept apart from the other settings.
+#[derive(Default)]
+struct Stores {
+    settings: Map<String, Value>,
+    connections: Map<String, Value>,
+    tabs: Map<String, Value>,
+    history: Map<String, Value>,
+    cloud: Map<String, Value>,
+    contents: BTreeMap<String, String>,
+}
+
+impl Stores {
+    fn store(&self, store: ConfigStore) -> &Map<String, Value> {
+        match store {
+            ConfigStore::Settings => &self.settings,
+            ConfigStore::Connections => &self.connections,
+            ConfigStore::Tabs => &self.tabs,
+            ConfigStore::History => &self.history,
+            ConfigStore::Cloud => &self.cloud,
+        }
+    }
+
+    fn store_mut(&mut self, store: ConfigStore) -> &mut Map<String, Value> {
+        match store {
+            ConfigStore::Settings => &mut self.settings,
+            ConfigStore::Connections => &mut self.connections,
+            ConfigStore::Tabs => &mut self.tabs,
+            ConfigStore::History => &mut self.history,
+            ConfigStore::Cloud => &mut self.cloud,
+        }
+    }
+}
+
+fn contents_directory() -> PathBuf {
+    get_config_stores_directory().join(CONTENTS_DIRECTORY)
+}
+
+/// Tab ids are used as file names, so only plain identifiers are accepted
+fn is_file_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn contents_path(id: &str) -> PathBuf {
+    contents_directory().join(format!("{}.{}", id, CONTENTS_EXTENSION))
+}
+
+/// Every connection object in the connections store, including the sandbox
+fn connection_objects(connections: &mut Map<String, Value>) -> Vec<&mut Map<String, Value>> {
+    let mut objects = Vec::new();
+
+    for (key, value) in connections.iter_mut() {
+        match (key.as_str(), value) {
+            ("connections", Value::Array(list)) => {
+                objects.extend(list.iter_mut().filter_map(Value::as_object_mut))
+            }
+            ("sandbox", Value::Object(sandbox)) => objects.push(sandbox),
+            _ => {}
+        }
+    }
+
+    objects
+}
+
+fn object_id(object: &Map<String, Value>) -> Option<String> {
+    object.get("id").and_then(Value::as_str).map(str::to_owned)
+}
+
+/// Split a config document into its stores
+fn split(mut document: Map<String, Value>) -> Stores {
+    let mut stores = Stores::default();
+    let mut histories = Map::new();
+
+    for key in ["connections", "sandbox"] {
+        if let Some(value) = document.remove(key) {
+            stores.connections.insert(key.into(), value);
+        }
+    }
+
+    if let Some(commands) = document.remove("commandHistory") {
+        stores.history.insert("commandHistory".into(), commands);
+    }
+
+    for connection in connection_objects(&mut stores.connections) {
+        let Some(id) = object_id(connection) else {
+            continue;
+        };
+
+        let mut tabs = Map::new();
+
+        for field in ["queries", "activeQuery"] {
+            if let Some(value) = connection.remove(field) {
+                tabs.insert(field.into(), value);
+            }
+        }
+
+        if let Some(Value::Array(queries)) = tabs.get_mut("queries") {
+            for query in queries.iter_mut().filter_map(Value::as_object_mut) {
+                let tab = object_id(query).filter(|id| is_file_id(id));
+                let is_config = query.get("type").and_then(Value::as_str) == Some("config");
+
+                if let (Some(tab), true) = (tab, is_config) {
+                    if let Some(Value::String(content)) = query.remove("query") {
+                        stores.contents.insert(tab, content);
+                    }
+                }
+            }
+        }
+
+        if !tabs.is_empty() {
+            stores.tabs.insert(id.clone(), Value::Object(tabs));
+        }
+
+        if let Some(history) = connection.remove("queryHistory") {
+            histories.insert(id, history);
+        }
+    }
+
+    if !histories.is_empty() {
+        stores
+            .history
+            .insert("connections".into(), Value::Object(histories));
+    }
+
+    if let Some(Value::Object(settings)) = document.get_mut("settings") {
+        if let Some(Value::Object(cloud)) = settings.get_mut("cloud") {
+            stores.cloud = std::mem::take(cloud);
+            settings.remove("cloud");
+        }
+    }
+
+    stores.settings = document;
+    stores
+}
+
+/// Join stores back into a single config document
+fn join(mut stores: Stores) -> Map<String, Value> {
+    let mut histories = match stores.history.remove("connections") {
+        Some(Value::Object(histories)) => histories,
+        _ => Map::new(),
+    };
+
+    let mut tabs = std::mem::take(&mut stores.tabs);
+
+    for connection in connection_objects(&mut stores.connections) {
+        let Some(id) = object_id(connection) else {
+            continue;
+        };
+
+        if let Some(Value::Object(fields)) = tabs.remove(&id) {
+            connection.extend(fields);
+        }
+
+        if let Some(Value::Array(queries)) = connection.get_mut("queries") {
+            for query in queries.iter_mut().filter_map(Value::as_object_mut) {
+                let content = object_id(query).and_then(|tab| stores.contents.remove(&tab));
+
+                if let Some(content) = content {
+                    query.insert("query".into(), Value::String(content));
+                } else if !query.contains_key("query") {
+                    query.insert("query".into(), Value::String(String::new()));
+                }
+            }
+        }
+
+        if let Some(history) = histories.remove(&id) {
+            connection.insert("queryHistory".into(), history);
+        }
+    }
+
+    let mut document = stores.settings;
+
+    if !stores.cloud.is_empty() {
+        let settings = document
+            .entry("settings")
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        if let Value::Object(settings) = settings {
+            settings.insert("cloud".into(), Value::Object(stores.cloud));
+        }
+    }
+
+    document.extend(stores.connections);
+    document.extend(stores.history);
+    document
+}
+
+/// The part of the newest valid version backup held by a store, migrated to
+/// the current config version
+fn read_backup_store(store: ConfigStore) -> Option<(PathBuf, Map<String, Value>)> {
+    let candidates = config_backup_paths().map(|path| (RecoverySource::Backup, path));
+    let (_, path, contents) = newest_valid_config(candidates)?;
+    let mut document = parse_config(&contents).ok()?;
+
+    if let Some(version) = document.get("configVersion").and_then(Value::as_u64) {
+        run_steps(&mut document, version, CONFIG_VERSION).ok()?;
+    }
+
+    let mut stores = split(document);
+
+    Some((path, std::mem::take(stores.store_mut(store))))
+}
+
+/// Read a store, recovering it from its previous copy or the newest version
+/// backup when corrupted
+fn read_store(
+    store: ConfigStore,
+    recover: bool,
+) -> Result<(Map<String, Value>, Option<ConfigRecovery>), ConfigError> {
+    let path = store.path();
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok((Map::new(), None)),
+        Err(err) => return Err(ConfigError::io("read", err)),
+    };
+
+    let reason = match parse_config(&contents) {
+        Ok(map) => return Ok((map, None)),
+        Err(reason) if !recover => {
+            return Err(ConfigError::new(ConfigErrorKind::InvalidJson, reason))
+        }
+        Err(reason) => reason,
+    };
+
+    let previous = sibling_path(&path, "previous");
+    let quarantined = path.with_extension(format!(
+        "corrupt-{}.json",
+        OffsetDateTime::now_utc().unix_timestamp()
+    ));
+
+    fs::rename(&path, &quarantined).map_err(|e| ConfigError::io("quarantine", e))?;
+
+    let restored = fs::read_to_string(&previous)
+        .ok()
+        .and_then(|contents| parse_config(&contents).ok())
+        .map(|map| (RecoverySource::Previous, previous, map))
+        .or_else(|| {
+            read_backup_store(store).map(|(path, map)| (RecoverySource::Backup, path, map))
+        });
+
+    let (source, path, restored) = match restored {
+        Some((source, path, map)) => (source, Some(path), map),
+        None => (RecoverySource::Default, None, Map::new()),
+    };
+
+    warn!(
+        "Config store {:?} was corrupted ({}), moved to {} and recovered from {:?}",
+        store,
+        reason,
+        quarantined.display(),
+        source
+    );
+
+    let recovery = ConfigRecovery {
+        reason,
+        quarantined: quarantined.to_string_lossy().into_owned(),
+        source,
+        path: path.map(|path| path.to_string_lossy().into_owned()),
+    };
+
+    Ok((restored, Some(recovery)))
+}
+
+/// Read the queries of all tabs
+fn read_contents() -> BTreeMap<String, String> {
+    let Ok(entries) = fs::read_dir(contents_directory()) else {
+        return BTreeMap::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == CONTENTS_EXTENSION))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.to_owned();
+            let content = fs::read_to_string(&path).ok()?;
+
+            Some((id, content))
+        })
+        .collect()
+}
+
+///
+/// Read all stores and join them into a single config document. When
+/// recovering, corrupted stores are replaced instead of failing.
+///
+pub fn read_stores(recover: bool) -> Result<LoadedConfig, ConfigError> {
+    let mut stores = Stores::default();
+    let mut recovery = None;
+
+    for store in ConfigStore::ALL {
+        let (contents, recovered) = read_store(store, recover)?;
+
+        *stores.store_mut(store) = contents;
+        recovery = recovery.or(recovered);
+    }
+
+    stores.contents = read_contents();
+
+    let mut document = join(stores);
+
+    reveal_document(&mut document);
+
+    Ok(LoadedConfig {
+        config: Value::Object(document).to_string(),
+        recovery,
+        revision: 0,
+    })
+}
+
+/// Write a file unless it already has the given contents
+fn write_if_changed(path: &Path, contents: &[u8]) -> Result<(), ConfigError> {
+    if fs::read(path).is_ok_and(|current| current == contents) {
+        return Ok(());
+    }
+
+    write_atomic(path, contents).map_err(|e| ConfigError::io("write", e))
+}
+
+/// Write a single store unless it is unchanged
+fn write_store(store: ConfigStore, contents: &Map<String, Value>) -> Result<(), ConfigError> {
+    let mut contents = serde_json::to_string_pretty(contents)
+        .map_err(|e| ConfigError::new(ConfigErrorKind::Io, e.to_string()))?;
+
+    contents.push('\n');
+
+    write_if_changed(&store.path(), contents.as_bytes())
+}
+
+///
+/// Split a config document into its stores and write those which changed,
+/// removing the queries of closed tabs
+///
+pub fn write_stores(document: &Map<String, Value>) -> Result<(), ConfigError> {
+    write_selected_stores(document, &BTreeSet::from(ConfigStore::ALL))
+}
+
+///
+/// Write only the given stores of a config document. The queries of tabs
+/// are written along with the tabs store.
+///
+pub fn write_selected_stores(
+    document: &Map<String, Value>,
+    selected: &BTreeSet<ConfigStore>,
+) -> Result<(), ConfigError> {
+    let mut document = document.clone();
+
+    seal_document(&mut document, true)?;
+
+    let stores = split(document);
+
+    for &store in selected {
+        write_store(store, stores.store(store))?;
+    }
+
+    if !selected.contains(&ConfigStore::Tabs) {
+        return Ok(());
+    }
+
+    for (id, content) in &stores.contents {
+        write_if_changed(&contents_path(id), content.as_bytes())?;
+    }
+
+    let closed = fs::read_dir(contents_directory())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|e| e == CONTENTS_EXTENSION)
+                && path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|id| !stores.contents.contains_key(id))
+        });
+
+    for path in closed {
+        let _ = fs::remove_file(sibling_path(&path, "previous"));
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(())
+}
+
+/// The legacy config file once it was split into stores
+pub fn migrated_config_path() -> PathBuf {
+    sibling_path(&get_config_path(), "migrated")
+}
+
+///
+/// Split the legacy single file config into stores. The original file is
+/// kept as a backup afterwards, so it is not mistaken for the current config.
+///
+pub fn migrate_legacy_config() -> Result<Option<ConfigRecovery>, ConfigError> {
+    let legacy_path = get_config_path();
+    let has_stores = ConfigStore::ALL.iter().any(|store| store.path().exists());
+
+    if has_stores || !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    let LoadedConfig {
+        config, recovery, ..
+    } = read_legacy_config()?;
+
+    let document =
+        parse_config(&config).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    write_stores(&document)?;
+
+    fs::rename(&legacy_path, migrated_config_path()).map_err(|e| ConfigError::io("rename", e))?;
+
+    info!("Split the config file into separate stores");
+
+    Ok(recovery)
+}
+
+/// The current config, including changes which were not written yet
+pub fn current_document(current: &ConfigRevision) -> Result<Map<String, Value>, ConfigError> {
+    match &current.pending {
+        Some(document) => Ok(document.clone()),
+        None => {
+            let LoadedConfig { config, .. } = super::read_config()?;
+
+            parse_config(&config).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))
+        }
+    }
+}
+
+/// Write an updated config and notify all windows to reload it
+pub fn commit_stores(
+    app: &AppHandle,
+    window: &Window,
+    current: &mut ConfigRevision,
+    document: Map<String, Value>,
+) -> Result<u64, ConfigError> {
+    write_stores(&document)?;
+
+    current.pending = None;
+    current.dirty.clear();
+
+    Ok(notify_reload(app, window, current))
+}
+
+/// Record a new version of the stores and notify all windows to reload it
+fn notify_reload(app: &AppHandle, window: &Window, current: &mut ConfigRevision) -> u64 {
+    let revision = current.advance();
+
+    let _ = app.emit(
+        "config:changed",
+        ConfigChanged {
+            revision,
+            config: None,
+            patch: None,
+            source: Some(window.label().to_owned()),
+        },
+    );
+
+    revision
+}
+
+fn missing_tab() -> ConfigError {
+    ConfigError::new(ConfigErrorKind::NotFound, "Query tab does not exist")
+}
+
+///
+/// Read a single store, including changes which were not written yet
+///
+#[tauri::command]
+pub fn load_config_store(
+    state: State<ConfigSyncState>,
+    store: ConfigStore,
+) -> Result<String, ConfigError> {
+    let current = state.0.lock().unwrap();
+
+    let contents = match &current.pending {
+        Some(document) => split(document.clone()).store(store).clone(),
+        None => {
+            let (mut contents, _) = read_store(store, false)?;
+
+            // Stores share the layout of the document they were split from
+            reveal_document(&mut contents);
+            contents
+        }
+    };
+
+    Ok(Value::Object(contents).to_string())
+}
+
+///
+/// Replace a single store and notify all windows. Pending changes are
+/// written first, so they neither get lost nor overwrite the store.
+///
+#[tauri::command]
+pub fn save_config_store(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    store: ConfigStore,
+    contents: &str,
+    revision: Option<u64>,
+) -> Result<u64, ConfigError> {
+    let mut current = state.0.lock().unwrap();
+
+    current.check_revision(revision)?;
+
+    let mut contents =
+        parse_config(contents).map_err(|e| ConfigError::new(ConfigErrorKind::InvalidJson, e))?;
+
+    write_pending(&mut current)?;
+    seal_document(&mut contents, false)?;
+    write_store(store, &contents)?;
+
+    Ok(notify_reload(&app, &window, &mut current))
+}
+
+#[tauri::command]
+pub fn load_tab_content(state: State<ConfigSyncState>, tab: &str) -> Result<String, ConfigError> {
+    let current = state.0.lock().unwrap();
+
+    if let Some(document) = &current.pending {
+        return split(document.clone())
+            .contents
+            .remove(tab)
+            .ok_or_else(missing_tab);
+    }
+
+    if !is_file_id(tab) {
+        return Err(missing_tab());
+    }
+
+    fs::read_to_string(contents_path(tab)).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => missing_tab(),
+        _ => ConfigError::io("read", e),
+    })
+}
+
+#[tauri::command]
+pub fn save_tab_content(
+    app: AppHandle,
+    window: Window,
+    state: State<ConfigSyncState>,
+    tab: &str,
+    content: String,
+    revision: Option<u64>,
+) -> Result<u64, ConfigError> {
+    let mut current = state.0.lock().unwrap();
+
+    current.check_revision(revision)?;
+    write_pending(&mut current)?;
+
+    let path = contents_path(tab);
+
+    if !is_file_id(tab) || !path.exists() {
+        return Err(missing_tab());
+    }
+
+    write_if_changed(&path, content.as_bytes())?;
+
+    Ok(notify_reload(&app, &window, &mut current))
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    sync::Mutex,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use log::{error, info, warn};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{
+    patch::PatchOperation,
+    stores::{read_stores, write_selected_stores, ConfigStore},
+    ConfigError, ConfigErrorKind,
+};
+use crate::paths::get_config_stores_directory;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const WRITE_DELAY: Duration = Duration::from_millis(500);
+
+/// Identifies a version of the config stores on disk
+#[derive(Clone, Copy, PartialEq)]
+pub struct Fingerprint {
+    modified: SystemTime,
+    size: u64,
+    files: usize,
+}
+
+impl Fingerprint {
+    /// Combine the modification times and sizes of all config stores
+    pub fn current() -> Option<Self> {
+        let directory = get_config_stores_directory();
+        let mut fingerprint: Option<Self> = None;
+
+        let entries = fs::read_dir(&directory)
+            .into_iter()
+            .chain(fs::read_dir(directory.join("tabs")))
+            .flatten()
+            .filter_map(Result::ok);
+
+        for entry in entries {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+
+            let current = fingerprint.get_or_insert(Self {
+                modified,
+                size: 0,
+                files: 0,
+            });
+
+            current.modified = current.modified.max(modified);
+            current.size += metadata.len();
+            current.files += 1;
+        }
+
+        fingerprint
+    }
+}
+
+/// The revision of the config and the version of the stores on disk it
+/// corresponds to
+#[derive(Default)]
+pub struct ConfigRevision {
+    pub revision: u64,
+    pub fingerprint: Option<Fingerprint>,
+    /// Patched config which was not yet written to disk
+    pub pending: Option<Map<String, Value>>,
+    /// The stores changed by the pending config
+    pub dirty: BTreeSet<ConfigStore>,
+    pub flush_scheduled: bool,
+}
+
+impl ConfigRevision {
+    /// Record a new version of the config stores
+    pub fn advance(&mut self) -> u64 {
+        self.revision += 1;
+        self.fingerprint = Fingerprint::current();
+        self.revision
+    }
+
+    /// Reject a change based on an outdated revision
+    pub fn check_revision(&self, revision: Option<u64>) -> Result<(), ConfigError> {
+        if revision.is_some_and(|r| r != self.revision) {
+            return Err(ConfigError::new(
+                ConfigErrorKind::Conflict,
+                "Config was changed elsewhere since it was loaded",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ConfigSyncState(pub Mutex<ConfigRevision>);
+
+/// Broadcast to all windows when the config changed, containing either the
+/// complete config or the patch applied to the previous revision
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChanged {
+    pub revision: u64,
+    pub config: Option<String>,
+    pub patch: Option<Vec<PatchOperation>>,
+    pub source: Option<String>,
+}
+
+///
+/// Start the background thread watching the config stores for changes made
+/// outside of Surrealist
+///
+pub fn start_config_watcher(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let state = app.state::<ConfigSyncState>();
+        let mut current = state.0.lock().unwrap();
+        let fingerprint = Fingerprint::current();
+
+        if fingerprint.is_none() || fingerprint == current.fingerprint {
+            continue;
+        }
+
+        // Unwritten changes are about to replace the stores anyway
+        if current.pending.is_some() {
+            warn!("Config stores changed externally while changes were pending");
+            continue;
+        }
+
+        current.fingerprint = fingerprint;
+
+        // Partially written or invalid edits are picked up once fixed
+        let config = match read_stores(false) {
+            Ok(loaded) => loaded.config,
+            Err(err) => {
+                warn!("Ignoring external config change: {}", err);
+                continue;
+            }
+        };
+
+        current.revision += 1;
+
+        info!(
+            "Config stores changed externally (revision {})",
+            current.revision
+        );
+
+        let _ = app.emit(
+            "config:changed",
+            ConfigChanged {
+                revision: current.revision,
+                config: Some(config),
+                patch: None,
+                source: None,
+            },
+        );
+    });
+}
+
+///
+/// Write the pending config after a short delay, so that a burst of
+/// patches results in a single write
+///
+pub fn schedule_flush(app: &AppHandle, current: &mut ConfigRevision) {
+    if current.flush_scheduled {
+        return;
+    }
+
+    let app = app.clone();
+
+    current.flush_scheduled = true;
+
+    thread::spawn(move || {
+        thread::sleep(WRITE_DELAY);
+        flush_config(&app);
+    });
+}
+
+///
+/// Write the pending config changes to the stores they affect
+///
+pub fn write_pending(current: &mut ConfigRevision) -> Result<(), ConfigError> {
+    let Some(document) = current.pending.take() else {
+        return Ok(());
+    };
+
+    let dirty = std::mem::take(&mut current.dirty);
+
+    if let Err(err) = write_selected_stores(&document, &dirty) {
+        current.pending = Some(document);
+        current.dirty.extend(dirty);
+
+        return Err(err);
+    }
+
+    current.fingerprint = Fingerprint::current();
+
+    Ok(())
+}
+
+///
+/// Immediately write any pending config changes to disk
+///
+pub fn flush_config(app: &AppHandle) {
+    let state = app.state::<ConfigSyncState>();
+    let mut current = state.0.lock().unwrap();
+
+    current.flush_scheduled = false;
+
+    if let Err(err) = write_pending(&mut current) {
+        error!("{}", err);
+
+        let _ = app.emit("config:error", err);
+    }
+}
@@ -0,0 +1,853 @@
+use std::{collections::BTreeMap, fs, sync::Mutex};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::{
+    sibling_path,
+    stores::{current_document, migrated_config_path, write_stores, ConfigStore},
+    sync::{ConfigChanged, ConfigSyncState},
+    write_atomic, ConfigError, ConfigErrorKind,
+};
+use crate::{
+    get_app_handle,
+    paths::{get_config_backups_directory, get_config_path, get_data_directory, get_vault_path},
+};
+
+const REFERENCE_PREFIX: &str = "vault:";
+const AUTHENTICATION_SECRETS: [&str; 2] = ["password", "token"];
+const VAULT_KEY: &str = "vault-key";
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+/// How the key encrypting the secrets is protected
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VaultProtection {
+    Keyring,
+    Password,
+}
+
+/// An encrypted value along with its nonce
+#[derive(Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The parameters used to derive a key from the master password
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KdfParams {
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+/// The vault as stored on disk. Secrets are encrypted with a random key,
+/// which is either kept in the OS keyring or encrypted with a key derived
+/// from the master password.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultFile {
+    protection: VaultProtection,
+    kdf: Option<KdfParams>,
+    wrapped_key: Option<Sealed>,
+    secrets: BTreeMap<String, Sealed>,
+}
+
+/// The unlocked key, if any
+#[derive(Default)]
+pub struct Vault {
+    key: Option<Key>,
+    locked_by_user: bool,
+}
+
+#[derive(Default)]
+pub struct VaultState(pub Mutex<Vault>);
+
+/// The state of the vault as shown to the user
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStatus {
+    pub created: bool,
+    pub locked: bool,
+    pub protection: Option<VaultProtection>,
+    pub keyring_available: bool,
+}
+
+fn vault_error(message: &str) -> ConfigError {
+    ConfigError::new(ConfigErrorKind::Io, message)
+}
+
+fn locked_error() -> ConfigError {
+    ConfigError::new(
+        ConfigErrorKind::VaultLocked,
+        "Unlock the vault before changing passwords or tokens",
+    )
+}
+
+/// Returns whether a config value refers to a secret in the vault
+pub fn is_vault_reference(value: &str) -> bool {
+    value.starts_with(REFERENCE_PREFIX)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn keyring_entry() -> Option<keyring::Entry> {
+    let service = get_data_directory()
+        .file_name()?
+        .to_string_lossy()
+        .into_owned();
+
+    keyring::Entry::new(&service, VAULT_KEY).ok()
+}
+
+/// Returns whether an OS keyring can be used on this machine
+fn is_keyring_available() -> bool {
+    keyring_entry()
+        .is_some_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)))
+}
+
+/// Encrypt a value, binding it to the name it is stored under
+fn seal(key: &Key, name: &str, value: &[u8]) -> Result<Sealed, ConfigError> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key)
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: value,
+                aad: name.as_bytes(),
+            },
+        )
+        .map_err(|_| vault_error("Failed to encrypt secret"))?;
+
+    Ok(Sealed {
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    })
+}
+
+/// Decrypt a value, failing when it was tampered with or the key is wrong
+fn open(key: &Key, name: &str, sealed: &Sealed) -> Option<Vec<u8>> {
+    let nonce = from_hex(&sealed.nonce).filter(|n| n.len() == 12)?;
+    let ciphertext = from_hex(&sealed.ciphertext)?;
+
+    ChaCha20Poly1305::new(key)
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: name.as_bytes(),
+            },
+        )
+        .ok()
+}
+
+/// Derive the key protecting the vault key from the master password
+fn derive_key(password: &str, params: &KdfParams) -> Result<Key, ConfigError> {
+    let salt = from_hex(&params.salt).ok_or_else(|| vault_error("Vault is corrupted"))?;
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LENGTH),
+    )
+    .map_err(|_| vault_error("Vault is corrupted"))?;
+
+    let mut key = Key::default();
+
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &salt, &mut key)
+        .map_err(|_| vault_error("Failed to derive vault key"))?;
+
+    Ok(key)
+}
+
+fn read_vault_file() -> Result<Option<VaultFile>, ConfigError> {
+    match fs::read_to_string(get_vault_path()) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|_| vault_error("Vault is corrupted")),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(ConfigError::io("read vault", err)),
+    }
+}
+
+fn write_vault_file(file: &VaultFile) -> Result<(), ConfigError> {
+    let contents = serde_json::to_string_pretty(file)
+        .map_err(|e| ConfigError::new(ConfigErrorKind::Io, e.to_string()))?;
+
+    write_atomic(&get_vault_path(), contents.as_bytes())
+        .map_err(|e| ConfigError::io("write vault", e))
+}
+
+/// Recover the vault key using the keyring or the master password
+fn unwrap_key(file: &VaultFile, password: Option<&str>) -> Result<Key, ConfigError> {
+    let invalid = || {
+        ConfigError::new(
+            ConfigErrorKind::InvalidPassword,
+            "Incorrect master password",
+        )
+    };
+
+    let key = match file.protection {
+        VaultProtection::Keyring => keyring_entry()
+            .and_then(|entry| entry.get_password().ok())
+            .and_then(|hex| from_hex(&hex))
+            .ok_or_else(|| {
+                ConfigError::new(
+                    ConfigErrorKind::KeyringUnavailable,
+                    "The vault key could not be read from the keyring",
+                )
+            })?,
+        VaultProtection::Password => {
+            let password = password.ok_or_else(invalid)?;
+            let (Some(params), Some(wrapped)) = (&file.kdf, &file.wrapped_key) else {
+                return Err(vault_error("Vault is corrupted"));
+            };
+
+            open(&derive_key(password, params)?, VAULT_KEY, wrapped).ok_or_else(invalid)?
+        }
+    };
+
+    if key.len() != KEY_LENGTH {
+        return Err(vault_error("Vault is corrupted"));
+    }
+
+    Ok(*Key::from_slice(&key))
+}
+
+impl Vault {
+    /// Unlock a keyring protected vault, unless it was locked by the user
+    fn auto_unlock(&mut self, file: &VaultFile) {
+        if self.key.is_none() && !self.locked_by_user && file.protection == VaultProtection::Keyring
+        {
+            self.key = unwrap_key(file, None).ok();
+        }
+    }
+}
+
+/// Every secret field in the config, along with the name it is stored under
+fn secret_fields(document: &mut Map<String, Value>) -> Vec<(String, &mut Value)> {
+    fn authentication<'a>(
+        prefix: String,
+        auth: Option<&'a mut Value>,
+        fields: &mut Vec<(String, &'a mut Value)>,
+    ) {
+        let Some(Value::Object(auth)) = auth else {
+            return;
+        };
+
+        for (name, value) in auth.iter_mut() {
+            if AUTHENTICATION_SECRETS.contains(&name.as_str()) {
+                fields.push((format!("{}/{}", prefix, name), value));
+            }
+        }
+    }
+
+    fn identified<'a>(
+        kind: &str,
+        field: &str,
+        object: &'a mut Value,
+        fields: &mut Vec<(String, &'a mut Value)>,
+    ) {
+        let Value::Object(object) = object else {
+            return;
+        };
+
+        if let Some(id) = object.get("id").and_then(Value::as_str).map(str::to_owned) {
+            authentication(format!("{}/{}", kind, id), object.get_mut(field), fields);
+        }
+    }
+
+    let mut fields = Vec::new();
+
+    for (key, value) in document.iter_mut() {
+        match (key.as_str(), value) {
+            ("connections", Value::Array(connections)) => {
+                for connection in connections {
+                    identified("connection", "authentication", connection, &mut fields);
+                }
+            }
+            ("sandbox", sandbox) => {
+                identified("connection", "authentication", sandbox, &mut fields)
+            }
+            ("settings", Value::Object(settings)) => {
+                for (category, value) in settings.iter_mut() {
+                    match (category.as_str(), value) {
+                        ("serving", Value::Object(serving)) => {
+                            if let Some(password) = serving.get_mut("password") {
+                                fields.push(("serving/password".into(), password));
+                            }
+                        }
+                        ("templates", Value::Object(templates)) => {
+                            if let Some(Value::Array(list)) = templates.get_mut("list") {
+                                for template in list {
+                                    identified("template", "values", template, &mut fields);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
+/// The secrets referenced by the config backups, the previous copies of the
+/// stores and the legacy config, any of which may be restored later
+fn referenced_elsewhere() -> Vec<String> {
+    let backups = fs::read_dir(get_config_backups_directory())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path());
+
+    let previous = [ConfigStore::Connections, ConfigStore::Settings]
+        .map(|store| sibling_path(&store.path(), "previous"));
+
+    backups
+        .chain(previous)
+        .chain([get_config_path(), migrated_config_path()])
+        .filter_map(
+            |path| match serde_json::from_str(&fs::read_to_string(path).ok()?) {
+                Ok(Value::Object(document)) => Some(document),
+                _ => None,
+            },
+        )
+        .flat_map(|mut document| {
+            secret_fields(&mut document)
+                .into_iter()
+                .filter_map(|(_, field)| {
+                    let reference = field.as_str()?.strip_prefix(REFERENCE_PREFIX)?;
+
+                    Some(reference.to_owned())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+///
+/// Refuse a config holding secrets entered while the vault is locked, as
+/// they cannot be encrypted until it is unlocked
+///
+pub fn check_unlocked(document: &mut Map<String, Value>) -> Result<(), ConfigError> {
+    let app = get_app_handle();
+    let state = app.state::<VaultState>();
+    let mut vault = state.0.lock().unwrap();
+
+    let Some(file) = read_vault_file()? else {
+        return Ok(());
+    };
+
+    vault.auto_unlock(&file);
+
+    if vault.key.is_some() {
+        return Ok(());
+    }
+
+    let entered = secret_fields(document).into_iter().any(|(_, field)| {
+        field
+            .as_str()
+            .is_some_and(|value| !value.is_empty() && !is_vault_reference(value))
+    });
+
+    match entered {
+        true => Err(locked_error()),
+        false => Ok(()),
+    }
+}
+
+///
+/// Replace the secrets in a config with references to the vault, failing
+/// while the vault is locked. When pruning, secrets no longer referenced
+/// by the config or any copy of it are removed. Does nothing until a vault
+/// is created.
+///
+pub fn seal_document(document: &mut Map<String, Value>, prune: bool) -> Result<(), ConfigError> {
+    let app = get_app_handle();
+    let state = app.state::<VaultState>();
+
+    // The lock is held until the vault is written, so concurrent writes
+    // cannot drop each other's secrets
+    let mut vault = state.0.lock().unwrap();
+
+    let Some(mut file) = read_vault_file()? else {
+        return Ok(());
+    };
+
+    let mut referenced = Vec::new();
+    let mut changed = false;
+
+    vault.auto_unlock(&file);
+
+    for (name, field) in secret_fields(document) {
+        let Value::String(value) = field else {
+            continue;
+        };
+
+        if value.is_empty() {
+            continue;
+        }
+
+        if let Some(reference) = value.strip_prefix(REFERENCE_PREFIX) {
+            referenced.push(reference.to_owned());
+            continue;
+        }
+
+        let key = vault.key.as_ref().ok_or_else(locked_error)?;
+        let current = file.secrets.get(&name).and_then(|s| open(key, &name, s));
+
+        if current.as_deref() != Some(value.as_bytes()) {
+            file.secrets
+                .insert(name.clone(), seal(key, &name, value.as_bytes())?);
+            changed = true;
+        }
+
+        *field = Value::String(format!("{}{}", REFERENCE_PREFIX, name));
+        referenced.push(name);
+    }
+
+    if prune && vault.key.is_some() {
+        let count = file.secrets.len();
+
+        referenced.extend(referenced_elsewhere());
+
+        file.secrets.retain(|name, _| referenced.contains(name));
+        changed |= file.secrets.len() != count;
+    }
+
+    if changed {
+        write_vault_file(&file)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Replace the vault references in a config with their secrets. References
+/// are left in place while the vault is locked.
+///
+pub fn reveal_document(document: &mut Map<String, Value>) {
+    let app = get_app_handle();
+    let state = app.state::<VaultState>();
+    let mut vault = state.0.lock().unwrap();
+
+    let file = match read_vault_file() {
+        Ok(Some(file)) => file,
+        Ok(None) => return,
+        Err(err) => {
+            warn!("{}", err);
+            return;
+        }
+    };
+
+    vault.auto_unlock(&file);
+
+    for (_, field) in secret_fields(document) {
+        let Some(name) = field
+            .as_str()
+            .and_then(|v| v.strip_prefix(REFERENCE_PREFIX))
+        else {
+            continue;
+        };
+
+        let secret = vault.key.as_ref().and_then(|key| {
+            let value = open(key, name, file.secrets.get(name)?)?;
+
+            String::from_utf8(value).ok()
+        });
+
+        match secret {
+            Some(secret) => *field = Value::String(secret),
+            None if vault.key.is_some() => warn!("Secret {} is missing from the vault", name),
+            None => {}
+        }
+    }
+}
+
+/// Encrypt the secrets in a config file written before the vault existed
+fn reseal_file(path: &std::path::Path) {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return;
+    };
+
+    let Ok(Value::Object(mut document)) = serde_json::from_str(&contents) else {
+        return;
+    };
+
+    let result = seal_document(&mut document, false).and_then(|_| {
+        let contents = serde_json::to_string_pretty(&document)
+            .map_err(|e| ConfigError::new(ConfigErrorKind::Io, e.to_string()))?;
+
+        write_atomic(path, contents.as_bytes()).map_err(|e| ConfigError::io("write", e))
+    });
+
+    if let Err(err) = result {
+        warn!("Failed to encrypt secrets in {}: {}", path.display(), err);
+    }
+
+    let _ = fs::remove_file(sibling_path(path, "previous"));
+}
+
+/// Write the config again so its secrets are moved into the vault, and
+/// notify all windows to reload it
+fn rewrite_config(app: &AppHandle) -> Result<(), ConfigError> {
+    let state = app.state::<ConfigSyncState>();
+    let mut current = state.0.lock().unwrap();
+    let document = current_document(&current)?;
+
+    write_stores(&document)?;
+
+    current.pending = None;
+    current.dirty.clear();
+
+    let revision = current.advance();
+
+    let _ = app.emit(
+        "config:changed",
+        ConfigChanged {
+            revision,
+            config: None,
+            patch: None,
+            source: None,
+        },
+    );
+
+    Ok(())
+}
+
+///
+/// Build a vault holding the given secrets, protecting its key with the
+/// master password or, without one, the OS keyring
+///
+fn protect_key(
+    key: &Key,
+    password: Option<String>,
+    secrets: BTreeMap<String, Sealed>,
+) -> Result<VaultFile, ConfigError> {
+    let file = match password.filter(|p| !p.is_empty()) {
+        Some(password) => {
+            let mut salt = [0u8; SALT_LENGTH];
+
+            OsRng.fill_bytes(&mut salt);
+
+            let params = KdfParams {
+                salt: to_hex(&salt),
+                memory_kib: Params::DEFAULT_M_COST,
+                iterations: Params::DEFAULT_T_COST,
+                parallelism: Params::DEFAULT_P_COST,
+            };
+
+            let wrapped = seal(&derive_key(&password, &params)?, VAULT_KEY, key)?;
+
+            VaultFile {
+                protection: VaultProtection::Password,
+                kdf: Some(params),
+                wrapped_key: Some(wrapped),
+                secrets,
+            }
+        }
+        None => {
+            keyring_entry()
+                .and_then(|entry| entry.set_password(&to_hex(key)).ok())
+                .ok_or_else(|| {
+                    ConfigError::new(
+                        ConfigErrorKind::KeyringUnavailable,
+                        "No keyring is available, a master password is required",
+                    )
+                })?;
+
+            VaultFile {
+                protection: VaultProtection::Keyring,
+                kdf: None,
+                wrapped_key: None,
+                secrets,
+            }
+        }
+    };
+
+    Ok(file)
+}
+
+#[tauri::command]
+pub fn get_vault_status(state: State<VaultState>) -> Result<VaultStatus, ConfigError> {
+    let mut vault = state.0.lock().unwrap();
+    let file = read_vault_file()?;
+
+    if let Some(file) = &file {
+        vault.auto_unlock(file);
+    }
+
+    Ok(VaultStatus {
+        created: file.is_some(),
+        locked: file.is_some() && vault.key.is_none(),
+        protection: file.map(|f| f.protection),
+        keyring_available: is_keyring_available(),
+    })
+}
+
+///
+/// Create the vault and move all secrets into it. Without a master password
+/// the vault key is kept in the OS keyring.
+///
+#[tauri::command]
+pub async fn create_vault(app: AppHandle, password: Option<String>) -> Result<(), ConfigError> {
+    let protection = {
+        let state = app.state::<VaultState>();
+        let mut vault = state.0.lock().unwrap();
+
+        if read_vault_file()?.is_some() {
+            return Err(ConfigError::new(
+                ConfigErrorKind::Conflict,
+                "A vault was already created",
+            ));
+        }
+
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let file = protect_key(&key, password, BTreeMap::new())?;
+
+        write_vault_file(&file)?;
+
+        *vault = Vault {
+            key: Some(key),
+            ..Vault::default()
+        };
+
+        file.protection
+    };
+
+    rewrite_config(&app)?;
+
+    // Remove plain text copies of the secrets left behind by earlier writes
+    for path in [
+        ConfigStore::Connections.path(),
+        ConfigStore::Settings.path(),
+    ] {
+        let _ = fs::remove_file(sibling_path(&path, "previous"));
+    }
+
+    let _ = fs::remove_file(sibling_path(&get_config_path(), "previous"));
+
+    let backups = fs::read_dir(get_config_backups_directory())
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path());
+
+    for path in backups.chain([get_config_path(), migrated_config_path()]) {
+        reseal_file(&path);
+    }
+
+    info!("Created vault protected by {:?}", protection);
+
+    Ok(())
+}
+
+fn missing_vault() -> ConfigError {
+    ConfigError::new(ConfigErrorKind::NotFound, "No vault was created")
+}
+
+#[tauri::command]
+pub async fn unlock_vault(app: AppHandle, password: Option<String>) -> Result<(), ConfigError> {
+    {
+        let state = app.state::<VaultState>();
+        let mut vault = state.0.lock().unwrap();
+        let file = read_vault_file()?.ok_or_else(missing_vault)?;
+        let key = unwrap_key(&file, password.as_deref())?;
+
+        vault.key = Some(key);
+        vault.locked_by_user = false;
+    }
+
+    rewrite_config(&app)
+}
+
+///
+/// Protect the unlocked vault with a new master password, or with the OS
+/// keyring when no password is given
+///
+#[tauri::command]
+pub async fn change_vault_protection(
+    app: AppHandle,
+    password: Option<String>,
+) -> Result<(), ConfigError> {
+    let state = app.state::<VaultState>();
+    let vault = state.0.lock().unwrap();
+    let file = read_vault_file()?.ok_or_else(missing_vault)?;
+    let key = vault.key.ok_or_else(locked_error)?;
+    let updated = protect_key(&key, password, file.secrets)?;
+
+    write_vault_file(&updated)?;
+
+    // The key is no longer needed in the keyring once a password protects it
+    if file.protection == VaultProtection::Keyring && updated.protection != file.protection {
+        if let Some(entry) = keyring_entry() {
+            let _ = entry.delete_credential();
+        }
+    }
+
+    info!("Changed vault protection to {:?}", updated.protection);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_vault(app: AppHandle) -> Result<(), ConfigError> {
+    let state = app.state::<ConfigSyncState>();
+    let mut current = state.0.lock().unwrap();
+
+    // Pending changes are written while their secrets can still be encrypted
+    if let Some(document) = &current.pending {
+        write_stores(document)?;
+
+        current.pending = None;
+        current.dirty.clear();
+    }
+
+    {
+        let vault = app.state::<VaultState>();
+        let mut vault = vault.0.lock().unwrap();
+
+        vault.key = None;
+        vault.locked_by_user = true;
+    }
+
+    let revision = current.advance();
+
+    let _ = app.emit(
+        "config:changed",
+        ConfigChanged {
+            revision,
+            config: None,
+            patch: None,
+            source: None,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn finds_secret_fields() {
+        let Value::Object(mut document) = json!({
+            "connections": [
+                { "id": "a", "authentication": { "username": "root", "password": "secret" } },
+                { "authentication": { "password": "unnamed" } }
+            ],
+            "sandbox": { "id": "sandbox", "authentication": { "token": "abc" } },
+            "settings": {
+                "serving": { "username": "root", "password": "serve" },
+                "templates": { "list": [{ "id": "t", "values": { "password": "template" } }] }
+            }
+        }) else {
+            unreachable!()
+        };
+
+        let mut fields: Vec<_> = secret_fields(&mut document)
+            .into_iter()
+            .map(|(name, value)| (name, value.clone()))
+            .collect();
+
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            fields,
+            vec![
+                ("connection/a/password".into(), json!("secret")),
+                ("connection/sandbox/token".into(), json!("abc")),
+                ("serving/password".into(), json!("serve")),
+                ("template/t/password".into(), json!("template")),
+            ]
+        );
+    }
+
+    #[test]
+    fn seals_and_opens_secrets() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let sealed = seal(&key, "serving/password", b"secret").unwrap();
+
+        assert_eq!(
+            open(&key, "serving/password", &sealed).as_deref(),
+            Some(&b"secret"[..])
+        );
+        assert_eq!(open(&key, "connection/a/password", &sealed), None);
+        assert_eq!(
+            open(
+                &ChaCha20Poly1305::generate_key(&mut OsRng),
+                "serving/password",
+                &sealed
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_secrets() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let mut sealed = seal(&key, "serving/password", b"secret").unwrap();
+
+        let flipped = match sealed.ciphertext.starts_with('0') {
+            true => "1",
+            false => "0",
+        };
+
+        sealed.ciphertext.replace_range(0..1, flipped);
+
+        assert_eq!(open(&key, "serving/password", &sealed), None);
+    }
+
+    #[test]
+    fn wraps_key_with_password() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let params = KdfParams {
+            salt: to_hex(&[7; SALT_LENGTH]),
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+
+        let wrapped = seal(&derive_key("password", &params).unwrap(), VAULT_KEY, &key).unwrap();
+        let file = VaultFile {
+            protection: VaultProtection::Password,
+            kdf: Some(params),
+            wrapped_key: Some(wrapped),
+            secrets: BTreeMap::new(),
+        };
+
+        assert_eq!(unwrap_key(&file, Some("password")).unwrap(), key);
+        assert!(matches!(
+            unwrap_key(&file, Some("wrong")).map_err(|e| e.kind),
+            Err(ConfigErrorKind::InvalidPassword)
+        ));
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use time::OffsetDateTime;
+
+use super::{
+    client::{escape_ident, execute_sql, info_keys, SqlTarget},
+    files, spawn_surreal_command,
+    transfer::{connection_arguments, start_transfer, TransferKind, TransferOptions},
+    DatabaseState,
+};
+use crate::{
+    config::{
+        patch::{update_config, PatchOperation},
+        stores::current_document,
+        sync::ConfigSyncState,
+    },
+    paths::get_database_backups_directory,
+};
+
+const MANIFEST_FILE: &str = "backup.json";
+const SETTINGS_POINTER: &str = "/settings/serving/backups";
+const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+
+/// The schedule and retention rules for automatic backups
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    pub keep_last: usize,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 60,
+            keep_last: 10,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// The state of the backup scheduler
+#[derive(Default)]
+pub struct BackupState {
+    pub settings: Mutex<BackupSettings>,
+    pub last_run: Mutex<Option<Instant>>,
+    pub running: AtomicBool,
+}
+
+/// A single exported database within a backup
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    pub namespace: String,
+    pub database: String,
+    pub file: String,
+    pub size: u64,
+}
+
+/// A backup of every database in the local instance at a point in time
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupRun {
+    pub id: String,
+    pub created_at: i64,
+    pub files: Vec<BackupFile>,
+}
+
+/// Read the backup settings persisted in the config
+fn load_settings(app: &AppHandle) -> BackupSettings {
+    let state = app.state::<ConfigSyncState>();
+    let current = state.0.lock().unwrap();
+
+    current_document(&current)
+        .ok()
+        .and_then(|document| Value::Object(document).pointer(SETTINGS_POINTER).cloned())
+        .and_then(|settings| serde_json::from_value(settings).ok())
+        .unwrap_or_default()
+}
+
+/// Start the background thread taking periodic backups of the local instance
+pub fn start_backup_scheduler(app: AppHandle) {
+    *app.state::<BackupState>().settings.lock().unwrap() = load_settings(&app);
+
+    thread::spawn(move || loop {
+        thread::sleep(SCHEDULER_TICK);
+
+        let state = app.state::<BackupState>();
+        let settings = state.settings.lock().unwrap().clone();
+
+        if !settings.enabled || app.state::<DatabaseState>().0.lock().unwrap().is_none() {
+            continue;
+        }
+
+        let interval = Duration::from_secs(settings.interval_minutes.max(1) * 60);
+        let is_due = state
+            .last_run
+            .lock()
+            .unwrap()
+            .is_none_or(|last| last.elapsed() >= interval);
+
+        if is_due {
+            perform_backup(&app);
+        }
+    });
+}
+
+/// Take a backup, apply the retention rules and notify all windows
+fn perform_backup(app: &AppHandle) {
+    let state = app.state::<BackupState>();
+
+    if state.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    *state.last_run.lock().unwrap() = Some(Instant::now());
+
+    match run_backup(app) {
+        Ok(run) => {
+            info!("Created backup {} of {} databases", run.id, run.files.len());
+
+            let settings = state.settings.lock().unwrap().clone();
+
+            apply_retention(&settings);
+
+            app.emit("backup:complete", run)
+                .expect("backup result should be delivered");
+        }
+        Err(err) => {
+            error!("Backup failed: {}", err);
+
+            app.emit("backup:error", err)
+                .expect("backup error should be delivered");
+        }
+    }
+
+    state.running.store(false, Ordering::SeqCst);
+}
+
+/// The endpoint, credentials and executable of the running local instance
+fn local_instance(state: &DatabaseState) -> Result<(String, String, String, String), String> {
+    let process = state.0.lock().unwrap();
+    let process = process
+        .as_ref()
+        .ok_or("The local database is not running")?;
+
+    Ok((
+        process.endpoint(),
+        process.username.clone(),
+        process.password.clone(),
+        process.executable.clone(),
+    ))
+}
+
+/// Export every namespace and database of the running local instance
+fn run_backup(app: &AppHandle) -> Result<BackupRun, String> {
+    let (endpoint, username, password, executable) = local_instance(&app.state::<DatabaseState>())?;
+
+    let target = SqlTarget {
+        endpoint: &endpoint,
+        username: &username,
+        password: &password,
+        namespace: None,
+        database: None,
+    };
+
+    let mut databases = Vec::new();
+
+    tauri::async_runtime::block_on(async {
+        let root = execute_sql(&target, "INFO FOR ROOT;").await?;
+
+        for ns in info_keys(&root, "namespaces")? {
+            let ns_target = SqlTarget {
+                namespace: Some(&ns),
+                ..target
+            };
+
+            let info = execute_sql(&ns_target, "INFO FOR NS;").await?;
+
+            for db in info_keys(&info, "databases")? {
+                databases.push((ns.clone(), db));
+            }
+        }
+
+        Ok::<_, String>(())
+    })?;
+
+    let now = OffsetDateTime::now_utc();
+    let timestamp = format!(
+        "{:04}-{:02}-{:02}-{:02}-{:02}-{:02}",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+
+    // Backups taken within the same second receive a numbered suffix
+    let root = get_database_backups_directory();
+    let directory = files::unique_directory(&root, &timestamp);
+    let id = directory
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    fs::create_dir_all(&root)
+        .and_then(|_| fs::create_dir(&directory))
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let env = [("SURREAL_PASS", password.clone())];
+    let files = export_databases(
+        &directory,
+        databases,
+        &executable,
+        &env,
+        |namespace, database| {
+            vec![
+                "export".to_owned(),
+                "--conn".to_owned(),
+                endpoint.clone(),
+                "--user".to_owned(),
+                username.clone(),
+                "--ns".to_owned(),
+                namespace.to_owned(),
+                "--db".to_owned(),
+                database.to_owned(),
+            ]
+        },
+    );
+
+    let files = match files {
+        Ok(files) => files,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&directory);
+            return Err(err);
+        }
+    };
+
+    let run = BackupRun {
+        id,
+        created_at: now.unix_timestamp(),
+        files,
+    };
+
+    let manifest = serde_json::to_string_pretty(&run).map_err(|e| e.to_string())?;
+
+    fs::write(directory.join(MANIFEST_FILE), manifest)
+        .map_err(|e| format!("Failed to write backup manifest: {}", e))?;
+
+    Ok(run)
+}
+
+/// Export each database into its own file within the given directory
+fn export_databases<F>(
+    directory: &Path,
+    databases: Vec<(String, String)>,
+    executable: &str,
+    env: &[(&str, String)],
+    connection_args: F,
+) -> Result<Vec<BackupFile>, String>
+where
+    F: Fn(&str, &str) -> Vec<String>,
+{
+    let mut files = Vec::new();
+
+    for (index, (namespace, database)) in databases.into_iter().enumerate() {
+        // Names may contain characters which are not valid in file names, so
+        // the file is identified by its position and the manifest maps it back
+        let file = format!(
+            "{:03}-{}.{}.surql",
+            index + 1,
+            files::slugify(&namespace),
+            files::slugify(&database)
+        );
+        let path = directory.join(&file);
+        let mut args = connection_args(&namespace, &database);
+
+        args.push(path.to_string_lossy().into_owned());
+
+        let output = spawn_surreal_command(executable, &args, env)
+            .and_then(|child| child.wait_with_output())
+            .map_err(|e| format!("Failed to launch surreal: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to export {}/{}: {}",
+                namespace,
+                database,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        files.push(BackupFile {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            namespace,
+            database,
+            file,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Read all backups, newest first
+fn read_backups() -> Vec<BackupRun> {
+    let Ok(entries) = fs::read_dir(get_database_backups_directory()) else {
+        return Vec::new();
+    };
+
+    let mut runs: Vec<BackupRun> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| fs::read_to_string(e.path().join(MANIFEST_FILE)).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    runs.sort_by_key(|r| Reverse(r.created_at));
+    runs
+}
+
+/// Select the backups to keep according to the retention rules
+fn select_retained(runs: &[BackupRun], settings: &BackupSettings) -> HashSet<String> {
+    let mut keep: HashSet<String> = runs
+        .iter()
+        .take(settings.keep_last)
+        .map(|r| r.id.clone())
+        .collect();
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    for run in runs {
+        let Ok(time) = OffsetDateTime::from_unix_timestamp(run.created_at) else {
+            continue;
+        };
+
+        let date = time.date();
+
+        if days.len() < settings.keep_daily && days.insert(date) {
+            keep.insert(run.id.clone());
+        }
+
+        if weeks.len() < settings.keep_weekly && weeks.insert((date.year(), date.iso_week())) {
+            keep.insert(run.id.clone());
+        }
+    }
+
+    keep
+}
+
+/// Delete all backups no longer covered by the retention rules
+fn apply_retention(settings: &BackupSettings) {
+    let runs = read_backups();
+    let keep = select_retained(&runs, settings);
+
+    for run in runs.iter().filter(|r| !keep.contains(&r.id)) {
+        info!("Pruning backup {}", run.id);
+
+        let _ = fs::remove_dir_all(backup_directory(&run.id));
+    }
+}
+
+fn backup_directory(id: &str) -> PathBuf {
+    get_database_backups_directory().join(Path::new(id).file_name().unwrap_or_default())
+}
+
+///
+/// Update the backup schedule and retention rules, persisting them in the
+/// config so they survive a restart
+///
+#[tauri::command]
+pub fn configure_backups(
+    app: AppHandle,
+    state: State<BackupState>,
+    settings: BackupSettings,
+) -> Result<(), String> {
+    let value = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+
+    update_config(
+        &app,
+        vec![PatchOperation::Add {
+            path: SETTINGS_POINTER.to_owned(),
+            value,
+        }],
+    )
+    .map_err(|e| e.to_string())?;
+
+    *state.settings.lock().unwrap() = settings;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_backup_settings(state: State<BackupState>) -> BackupSettings {
+    state.settings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn list_backups() -> Vec<BackupRun> {
+    read_backups()
+}
+
+#[tauri::command]
+pub fn create_backup(app: AppHandle) {
+    thread::spawn(move || perform_backup(&app));
+}
+
+#[tauri::command]
+pub fn delete_backup(id: &str) -> Result<(), String> {
+    fs::remove_dir_all(backup_directory(id)).map_err(|e| format!("Failed to delete backup: {}", e))
+}
+
+///
+/// Restore a database from a backup, replacing the current database with
+/// an empty one before its contents are imported
+///
+#[tauri::command]
+pub async fn restore_backup(
+    window: Window,
+    database: State<'_, DatabaseState>,
+    id: &str,
+    namespace: &str,
+    db: &str,
+) -> Result<u32, String> {
+    let run = read_backups()
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or("Backup does not exist")?;
+
+    let file = run
+        .files
+        .iter()
+        .find(|f| f.namespace == namespace && f.database == db)
+        .ok_or("Backup does not contain this database")?;
+
+    let options = TransferOptions {
+        endpoint: None,
+        username: None,
+        password: None,
+        token: None,
+        namespace: namespace.to_owned(),
+        database: db.to_owned(),
+        executable: String::new(),
+    };
+
+    let connection = connection_arguments(&database, &options)?;
+    let path = backup_directory(&run.id).join(&file.file);
+    let (endpoint, username, password, _) = local_instance(&database)?;
+
+    info!("Restoring {}/{} from backup {}", namespace, db, run.id);
+
+    let target = SqlTarget {
+        endpoint: &endpoint,
+        username: &username,
+        password: &password,
+        namespace: None,
+        database: None,
+    };
+
+    let query = format!(
+        "DEFINE NAMESPACE IF NOT EXISTS {0}; USE NS {0}; REMOVE DATABASE IF EXISTS {1}; DEFINE DATABASE {1};",
+        escape_ident(namespace),
+        escape_ident(db)
+    );
+
+    let responses = execute_sql(&target, &query).await?;
+
+    if let Some(failed) = responses.iter().find(|r| !r.is_ok()) {
+        return Err(format!(
+            "Failed to clear {}/{} before restoring: {}",
+            namespace,
+            db,
+            failed.error()
+        ));
+    }
+
+    start_transfer(window, TransferKind::Import, connection, path)
+}
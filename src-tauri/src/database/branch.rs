@@ -0,0 +1,152 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::info;
+
+use super::{
+    files,
+    registry::{
+        find_local_database, is_storage_in_use, now_millis, update_local_databases, LocalBranch,
+        RegistryState,
+    },
+    stop_serve_process, DatabaseState,
+};
+use crate::paths::get_local_branches_directory;
+
+/// Stop the serve process when it uses the given directory, or refuse to continue
+fn release_storage(state: &DatabaseState, path: &Path, stop_running: bool) -> Result<(), String> {
+    if !is_storage_in_use(state, path) {
+        return Ok(());
+    }
+
+    if !stop_running {
+        return Err("The local database is currently being served".into());
+    }
+
+    info!("Stopping database to release {}", path.display());
+
+    stop_serve_process(state);
+
+    Ok(())
+}
+
+/// Modify the branches of a local database in the registry
+fn update_branches(
+    registry: &RegistryState,
+    id: &str,
+    update: impl FnOnce(&mut Vec<LocalBranch>),
+) -> Result<(), String> {
+    update_local_databases(registry, |list| {
+        let database = list
+            .iter_mut()
+            .find(|db| db.id == id)
+            .ok_or("Local database does not exist")?;
+
+        update(&mut database.branches);
+
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn create_local_branch(
+    state: tauri::State<DatabaseState>,
+    registry: tauri::State<RegistryState>,
+    id: &str,
+    name: &str,
+    stop_running: bool,
+) -> Result<LocalBranch, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("Branch name cannot be empty".into());
+    }
+
+    let database = find_local_database(id)?;
+
+    if database.branches.iter().any(|b| b.name == name) {
+        return Err(format!("A branch named {} already exists", name));
+    }
+
+    let source = PathBuf::from(&database.path);
+    let target = files::unique_directory(&get_local_branches_directory(id), name);
+
+    release_storage(&state, &source, stop_running)?;
+
+    info!("Branching {} into {}", database.name, name);
+
+    files::clone_directory(&source, &target)
+        .map_err(|e| format!("Failed to snapshot storage directory: {}", e))?;
+
+    let branch = LocalBranch {
+        name: name.to_owned(),
+        path: target.to_string_lossy().into_owned(),
+        created_at: now_millis(),
+    };
+
+    update_branches(&registry, id, |branches| branches.push(branch.clone()))?;
+
+    Ok(branch)
+}
+
+#[tauri::command]
+pub fn restore_local_branch(
+    state: tauri::State<DatabaseState>,
+    id: &str,
+    name: &str,
+    stop_running: bool,
+) -> Result<(), String> {
+    let database = find_local_database(id)?;
+    let branch = database
+        .branches
+        .iter()
+        .find(|b| b.name == name)
+        .ok_or("Branch does not exist")?;
+
+    let main_path = PathBuf::from(&database.path);
+    let branch_path = PathBuf::from(&branch.path);
+
+    if is_storage_in_use(&state, &branch_path) {
+        return Err("Cannot restore a branch while it is being served".into());
+    }
+
+    release_storage(&state, &main_path, stop_running)?;
+
+    info!("Rolling {} back to branch {}", database.name, name);
+
+    files::replace_directory(&branch_path, &main_path)
+        .map_err(|e| format!("Failed to restore branch: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_local_branch(
+    state: tauri::State<DatabaseState>,
+    registry: tauri::State<RegistryState>,
+    id: &str,
+    name: &str,
+) -> Result<(), String> {
+    let database = find_local_database(id)?;
+    let branch = database
+        .branches
+        .iter()
+        .find(|b| b.name == name)
+        .ok_or("Branch does not exist")?;
+
+    let path = PathBuf::from(&branch.path);
+
+    if is_storage_in_use(&state, &path) {
+        return Err("Cannot delete a branch while it is being served".into());
+    }
+
+    if path.exists() {
+        fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete branch: {}", e))?;
+    }
+
+    update_branches(&registry, id, |branches| {
+        branches.retain(|b| b.name != name)
+    })
+}
@@ -0,0 +1,172 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT},
+    Certificate,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::tls;
+
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The response of a single statement executed over the HTTP interface
+#[derive(Deserialize)]
+pub struct QueryResponse {
+    pub status: String,
+    #[serde(default)]
+    pub result: Value,
+}
+
+impl QueryResponse {
+    pub fn is_ok(&self) -> bool {
+        self.status == "OK"
+    }
+
+    /// The error message of a failed statement
+    pub fn error(&self) -> String {
+        match &self.result {
+            Value::String(message) => message.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// An HTTP client which trusts the local certificate authority
+pub fn http_client() -> reqwest::Client {
+    let authority =
+        tls::read_authority().and_then(|pem| Certificate::from_pem(pem.as_bytes()).ok());
+    let builder = reqwest::Client::builder();
+
+    match authority {
+        Some(certificate) => builder.add_root_certificate(certificate),
+        None => builder,
+    }
+    .build()
+    .unwrap_or_default()
+}
+
+/// Credentials and selection used to query a SurrealDB HTTP endpoint
+pub struct SqlTarget<'a> {
+    pub endpoint: &'a str,
+    pub username: &'a str,
+    pub password: &'a str,
+    pub namespace: Option<&'a str>,
+    pub database: Option<&'a str>,
+}
+
+///
+/// Execute a query against the `/sql` endpoint of a SurrealDB server
+///
+pub async fn execute_sql(
+    target: &SqlTarget<'_>,
+    query: &str,
+) -> Result<Vec<QueryResponse>, String> {
+    let mut headers = HeaderMap::new();
+
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+    if let Some(ns) = target.namespace {
+        let value = HeaderValue::from_str(ns).map_err(|_| "Invalid namespace name")?;
+
+        headers.insert("surreal-ns", value.clone());
+        headers.insert("NS", value);
+    }
+
+    if let Some(db) = target.database {
+        let value = HeaderValue::from_str(db).map_err(|_| "Invalid database name")?;
+
+        headers.insert("surreal-db", value.clone());
+        headers.insert("DB", value);
+    }
+
+    let response = http_client()
+        .post(format!("{}/sql", target.endpoint))
+        .basic_auth(target.username, Some(target.password))
+        .headers(headers)
+        .timeout(QUERY_TIMEOUT)
+        .body(query.to_owned())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach database: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        return Err(format!("Query failed with status {}: {}", status, body));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read query response: {}", e))?;
+
+    serde_json::from_str(&body).map_err(|e| format!("Failed to parse query response: {}", e))
+}
+
+/// Escape a namespace or database name for use in a query
+pub fn escape_ident(name: &str) -> String {
+    format!("⟨{}⟩", name.replace('\\', "\\\\").replace('⟩', "\\⟩"))
+}
+
+///
+/// List the names of the keys of an object returned by an INFO statement
+///
+pub fn info_keys(responses: &[QueryResponse], field: &str) -> Result<Vec<String>, String> {
+    let Some(response) = responses.first() else {
+        return Ok(Vec::new());
+    };
+
+    if !response.is_ok() {
+        return Err(response.error());
+    }
+
+    Ok(response
+        .result
+        .get(field)
+        .and_then(Value::as_object)
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default())
+}
+
+/// Poll the health endpoint until the server responds or stops
+pub fn wait_until_ready<F: Fn() -> bool>(endpoint: &str, is_running: F) -> bool {
+    wait_until(&format!("{}/health", endpoint), is_running, |_| true)
+}
+
+///
+/// Poll the given URL until it responds successfully with a body accepted
+/// by the check, giving up when the process stops or the timeout elapses
+///
+pub fn wait_until<F, C>(url: &str, is_running: F, check: C) -> bool
+where
+    F: Fn() -> bool,
+    C: Fn(&str) -> bool,
+{
+    let started = Instant::now();
+
+    while started.elapsed() < READY_TIMEOUT && is_running() {
+        let ready = tauri::async_runtime::block_on(async {
+            match http_client().get(url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    response.text().await.is_ok_and(|body| check(&body))
+                }
+                _ => false,
+            }
+        });
+
+        if ready {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(250));
+    }
+
+    false
+}
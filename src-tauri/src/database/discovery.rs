@@ -0,0 +1,177 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, read_dir, read_to_string},
+    time::Duration,
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::paths::get_instances_directory;
+
+const WELL_KNOWN_PORTS: [u16; 5] = [8000, 8001, 8080, 8888, 9000];
+const DEFAULT_RANGE: PortRange = PortRange {
+    start: 8000,
+    end: 8010,
+};
+const MAX_SCAN_PORTS: usize = 4096;
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// An inclusive range of ports to scan
+#[derive(Clone, Copy, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+/// A serve process started by Surrealist, recorded so other windows and
+/// later sessions can find it
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pidfile {
+    pub pid: u32,
+    pub port: u16,
+    pub tls: bool,
+}
+
+/// A SurrealDB server found on the local machine
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredServer {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub version: String,
+    pub healthy: bool,
+    pub pid: Option<u32>,
+}
+
+///
+/// Record a serve process started by Surrealist
+///
+pub fn write_pidfile(pid: u32, port: u16, tls: bool) {
+    let directory = get_instances_directory();
+    let content = serde_json::to_string(&Pidfile { pid, port, tls }).unwrap();
+
+    let result = fs::create_dir_all(&directory)
+        .and_then(|_| fs::write(directory.join(format!("{}.json", pid)), content));
+
+    if let Err(err) = result {
+        warn!("Failed to write pidfile: {}", err);
+    }
+}
+
+///
+/// Remove the record of a serve process once it exited
+///
+pub fn remove_pidfile(pid: u32) {
+    let _ = fs::remove_file(get_instances_directory().join(format!("{}.json", pid)));
+}
+
+/// Read the pidfiles of running processes, removing those left behind
+fn read_pidfiles() -> Vec<Pidfile> {
+    let Ok(entries) = read_dir(get_instances_directory()) else {
+        return Vec::new();
+    };
+
+    let pidfiles: Vec<Pidfile> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    let pids: Vec<Pid> = pidfiles.iter().map(|p| Pid::from_u32(p.pid)).collect();
+    let mut system = System::new();
+
+    system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+
+    pidfiles
+        .into_iter()
+        .filter(|p| {
+            let alive = system.process(Pid::from_u32(p.pid)).is_some();
+
+            if !alive {
+                remove_pidfile(p.pid);
+            }
+
+            alive
+        })
+        .collect()
+}
+
+/// Identify a SurrealDB server on the given port through its version endpoint
+async fn probe(client: reqwest::Client, port: u16) -> Option<DiscoveredServer> {
+    for tls in [false, true] {
+        let scheme = if tls { "https" } else { "http" };
+        let base = format!("{}://127.0.0.1:{}", scheme, port);
+
+        let Ok(response) = client.get(format!("{}/version", base)).send().await else {
+            continue;
+        };
+
+        let body = response.text().await.unwrap_or_default();
+        let version = body.trim().strip_prefix("surrealdb-")?;
+
+        let healthy = client
+            .get(format!("{}/health", base))
+            .send()
+            .await
+            .is_ok_and(|r| r.status().is_success());
+
+        return Some(DiscoveredServer {
+            host: "127.0.0.1".to_owned(),
+            port,
+            tls,
+            version: version.to_owned(),
+            healthy,
+            pid: None,
+        });
+    }
+
+    None
+}
+
+///
+/// Scan loopback ports and the pidfiles of Surrealist started processes for
+/// SurrealDB servers. When no ranges are given, a default range is scanned
+/// in addition to well known ports.
+///
+#[tauri::command]
+pub async fn discover_local_servers(ranges: Option<Vec<PortRange>>) -> Vec<DiscoveredServer> {
+    let pidfiles: BTreeMap<u16, u32> = read_pidfiles().iter().map(|p| (p.port, p.pid)).collect();
+    let mut ports: BTreeSet<u16> = WELL_KNOWN_PORTS.into_iter().collect();
+
+    ports.extend(pidfiles.keys());
+
+    for range in ranges.unwrap_or_else(|| vec![DEFAULT_RANGE]) {
+        ports.extend((range.start..=range.end).take(MAX_SCAN_PORTS));
+    }
+
+    // Certificates are not verified, as this only identifies servers
+    // and local instances commonly use self-signed certificates
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap_or_default();
+
+    let probes: Vec<_> = ports
+        .into_iter()
+        .take(MAX_SCAN_PORTS)
+        .map(|port| tauri::async_runtime::spawn(probe(client.clone(), port)))
+        .collect();
+
+    let mut servers = Vec::new();
+
+    for probe in probes {
+        if let Ok(Some(mut server)) = probe.await {
+            server.pid = pidfiles.get(&server.port).copied();
+            servers.push(server);
+        }
+    }
+
+    servers
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::shell;
+
+/// Compare two paths, resolving them first when they exist
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Compute the total size of all files within a directory
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => directory_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Recursively copy the contents of a directory into a new location
+pub fn copy_directory(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_directory(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy a directory using copy-on-write clones where the file system supports
+/// them, falling back to a regular recursive copy otherwise
+pub fn clone_directory(from: &Path, to: &Path) -> std::io::Result<()> {
+    if to.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "target directory already exists",
+        ));
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let from_str = from.to_string_lossy();
+    let to_str = to.to_string_lossy();
+
+    if let Some(copy_cmd) = shell::build_copy_command(&from_str, &to_str) {
+        let mut cmd_chain = Command::new(&copy_cmd[0]);
+
+        shell::configure_command(&mut cmd_chain);
+
+        let cloned = cmd_chain
+            .args(&copy_cmd[1..])
+            .status()
+            .is_ok_and(|s| s.success());
+
+        if cloned {
+            return Ok(());
+        }
+
+        let _ = fs::remove_dir_all(to);
+    }
+
+    copy_directory(from, to)
+}
+
+/// Replace the contents of a directory with a copy of another directory,
+/// restoring the original contents when the copy fails
+pub fn replace_directory(source: &Path, target: &Path) -> std::io::Result<()> {
+    let mut previous = target.as_os_str().to_owned();
+
+    previous.push(".previous");
+
+    let previous = PathBuf::from(previous);
+    let _ = fs::remove_dir_all(&previous);
+
+    if target.exists() {
+        fs::rename(target, &previous)?;
+    }
+
+    if let Err(err) = clone_directory(source, target) {
+        let _ = fs::remove_dir_all(target);
+        let _ = fs::rename(&previous, target);
+
+        return Err(err);
+    }
+
+    let _ = fs::remove_dir_all(&previous);
+
+    Ok(())
+}
+
+/// Find an unused directory within the root for the given display name,
+/// adding a numeric suffix when its slug is already taken
+pub fn unique_directory(root: &Path, name: &str) -> PathBuf {
+    let slug = slugify(name);
+    let mut path = root.join(&slug);
+    let mut counter = 2;
+
+    while path.exists() {
+        path = root.join(format!("{}-{}", slug, counter));
+        counter += 1;
+    }
+
+    path
+}
+
+/// Turn a display name into a file system friendly directory name
+pub fn slugify(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+
+    let slug = slug
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        "database".to_owned()
+    } else {
+        slug
+    }
+}
@@ -0,0 +1,146 @@
+use std::process::ExitStatus;
+
+use serde::{Deserialize, Serialize};
+
+/// The signal raised by a process aborting on a failed allocation
+#[cfg(target_os = "linux")]
+const SIGABRT: i32 = 6;
+
+#[cfg(target_os = "linux")]
+const TELEMETRY_VARIABLES: [&str; 3] = [
+    "SURREAL_TELEMETRY_PROVIDER",
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_PROTOCOL",
+];
+
+/// Resource limits and sandboxing applied to the serve process. Limits are
+/// only supported on Linux and refused on other platforms.
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServeLimits {
+    pub memory: Option<u64>,
+    pub open_files: Option<u64>,
+    pub niceness: Option<i32>,
+    #[serde(default)]
+    pub clear_env: bool,
+    pub working_directory: Option<String>,
+}
+
+/// Describes a serve process terminated because it exceeded a limit
+#[derive(Clone, Serialize)]
+pub struct LimitViolation {
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl ServeLimits {
+    ///
+    /// Build the shell prefix which applies the limits before replacing the
+    /// shell with the surreal process
+    ///
+    #[cfg(target_os = "linux")]
+    pub fn command_prefix(&self) -> Result<Vec<String>, String> {
+        let mut prefix: Vec<String> = Vec::new();
+
+        if let Some(memory) = self.memory {
+            let kilobytes = (memory / 1024).max(1).to_string();
+
+            prefix.extend(["ulimit".into(), "-v".into(), kilobytes, "&&".into()]);
+        }
+
+        if let Some(files) = self.open_files {
+            prefix.extend(["ulimit".into(), "-n".into(), files.to_string(), "&&".into()]);
+        }
+
+        let mut wrappers: Vec<String> = Vec::new();
+
+        if self.clear_env {
+            wrappers.extend([
+                "env".into(),
+                "-i".into(),
+                "PATH=\"$PATH\"".into(),
+                "HOME=\"$HOME\"".into(),
+                "SURREAL_EXPERIMENTAL_GRAPHQL=true".into(),
+            ]);
+
+            // Keep the trace collector configuration, expanding to nothing when unset
+            for name in TELEMETRY_VARIABLES {
+                wrappers.push(format!("${{{0}:+{0}=${0}}}", name));
+            }
+        }
+
+        if let Some(niceness) = self.niceness {
+            wrappers.extend(["nice".into(), "-n".into(), niceness.to_string()]);
+        }
+
+        if !prefix.is_empty() || !wrappers.is_empty() {
+            prefix.push("exec".into());
+            prefix.extend(wrappers);
+        }
+
+        Ok(prefix)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn command_prefix(&self) -> Result<Vec<String>, String> {
+        let has_limits = self.memory.is_some() || self.open_files.is_some();
+
+        if has_limits || self.niceness.is_some() || self.clear_env {
+            return Err("Resource limits are only supported on Linux".into());
+        }
+
+        Ok(Vec::new())
+    }
+
+    ///
+    /// Determine whether an exited serve process was terminated by one of
+    /// the configured limits. Allocations failing under the memory limit
+    /// abort the process, while running out of file descriptors makes it
+    /// exit with an error reporting so.
+    ///
+    #[cfg(target_os = "linux")]
+    pub fn detect_violation(
+        &self,
+        status: ExitStatus,
+        output: &[String],
+    ) -> Option<LimitViolation> {
+        use std::os::unix::process::ExitStatusExt;
+
+        if let Some(memory) = self.memory {
+            if status.signal() == Some(SIGABRT) {
+                return Some(LimitViolation {
+                    kind: "memory",
+                    message: format!(
+                        "SurrealDB was stopped after exceeding the memory limit of {} bytes",
+                        memory
+                    ),
+                });
+            }
+        }
+
+        if let Some(files) = self.open_files {
+            let failed = status.code().is_some_and(|code| code != 0);
+
+            if failed
+                && output
+                    .iter()
+                    .any(|line| line.contains("Too many open files"))
+            {
+                return Some(LimitViolation {
+                    kind: "openFiles",
+                    message: format!(
+                        "SurrealDB was stopped after exceeding the limit of {} open files",
+                        files
+                    ),
+                });
+            }
+        }
+
+        None
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn detect_violation(&self, _: ExitStatus, _: &[String]) -> Option<LimitViolation> {
+        None
+    }
+}
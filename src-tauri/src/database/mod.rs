@@ -1,6 +1,8 @@
-use log::info;
+use log::{info, warn};
 use std::{
+    collections::VecDeque,
     io::{BufRead, BufReader},
+    path::{Path, PathBuf},
     process::{Child, Command, Stdio},
     sync::Mutex,
     thread,
@@ -8,9 +10,61 @@ use std::{
 };
 use tauri::{Emitter, Manager};
 
+use limits::ServeLimits;
+use seed::SeedOptions;
+use tikv::TikvOptions;
+
+use crate::config::vault::is_vault_reference;
+
+pub mod backup;
+pub mod branch;
+mod client;
+pub mod discovery;
+mod files;
+pub mod limits;
+pub mod monitor;
+pub mod querylog;
+pub mod registry;
+pub mod scratch;
+pub mod seed;
 mod shell;
+pub mod storage;
+pub mod telemetry;
+pub mod terminal;
+pub mod tikv;
+pub mod tls;
+pub mod transfer;
+pub mod upgrade;
+
+/// A running local SurrealDB serve process
+pub struct ServeProcess {
+    pub child: Child,
+    pub storage: Option<PathBuf>,
+    pub port: u32,
+    pub username: String,
+    pub password: String,
+    pub executable: String,
+    pub limits: ServeLimits,
+    pub tls: bool,
+}
+
+impl ServeProcess {
+    /// The HTTP endpoint the process is listening on
+    pub fn endpoint(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+
+        format!("{}://127.0.0.1:{}", scheme, self.port)
+    }
 
-pub struct DatabaseState(pub Mutex<Option<Child>>);
+    /// Returns whether this process is serving from the given storage directory
+    pub fn uses_storage(&self, path: &Path) -> bool {
+        self.storage
+            .as_ref()
+            .is_some_and(|s| files::same_path(s, path))
+    }
+}
+
+pub struct DatabaseState(pub Mutex<Option<ServeProcess>>);
 
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
@@ -25,6 +79,11 @@ pub fn start_database(
     executable: &str,
     log_level: &str,
     legacy_compat: bool,
+    limits: Option<ServeLimits>,
+    seed: Option<SeedOptions>,
+    playground: Option<TikvOptions>,
+    tls: Option<bool>,
+    create_storage: Option<bool>,
 ) -> Result<(), String> {
     let mut process = state.0.lock().unwrap();
     let start_at = Instant::now();
@@ -37,9 +96,76 @@ pub fn start_database(
         return Ok(());
     }
 
+    if is_vault_reference(password) {
+        return Err("Unlock the vault to start serving".into());
+    }
+
+    if let Some(seed) = &seed {
+        seed.validate()?;
+    }
+
+    let is_upgrading = window
+        .state::<upgrade::UpgradeState>()
+        .is_upgrading(Path::new(storage.trim()));
+
+    if driver != "memory" && driver != "tikv" && is_upgrading {
+        return Err("The storage directory is currently being upgraded".into());
+    }
+
+    if let Some(playground) = playground {
+        let handle = window.app_handle().clone();
+        let username = username.to_owned();
+        let password = password.to_owned();
+        let executable = executable.to_owned();
+        let log_level = log_level.to_owned();
+
+        drop(process);
+
+        tikv::serve_with_playground(handle.clone(), playground, move |endpoint| {
+            start_database(
+                window,
+                handle.state(),
+                &username,
+                &password,
+                port,
+                "tikv",
+                endpoint,
+                &executable,
+                &log_level,
+                legacy_compat,
+                limits,
+                seed,
+                None,
+                tls,
+                None,
+            )
+        });
+
+        return Ok(());
+    }
+
     info!("Serving database");
 
+    let handle = window.app_handle();
+    let telemetry = telemetry::ensure_collector(handle)
+        .inspect_err(|err| warn!("Starting without trace collection: {}", err))
+        .ok();
+
+    handle
+        .state::<telemetry::TelemetryState>()
+        .traces
+        .lock()
+        .unwrap()
+        .clear();
+
+    let tls = match tls.unwrap_or_default() {
+        true => Some(tls::ensure_certificates()?),
+        false => None,
+    };
+
+    let limits = limits.unwrap_or_default();
     let child_result = start_surreal_process(ServeOptions {
+        host: "0.0.0.0",
         username,
         password,
         port,
@@ -48,6 +174,11 @@ pub fn start_database(
         executable,
         log_level,
         legacy_compat,
+        limits: &limits,
+        telemetry: telemetry.as_deref(),
+        tls: tls.as_ref(),
+        create_storage: create_storage.unwrap_or_default(),
+        exclusive: true,
     });
 
     let mut child_proc = match child_result {
@@ -61,23 +192,65 @@ pub fn start_database(
         }
     };
 
+    let pid = child_proc.id();
     let output = child_proc.stderr.take().unwrap();
+    let storage_dir = match driver {
+        "memory" | "tikv" => None,
+        _ => Some(PathBuf::from(storage.trim())),
+    };
 
-    *process = Some(child_proc);
+    if let Some(dir) = &storage_dir {
+        registry::touch_local_database(&handle.state(), dir);
+    }
+
+    *process = Some(ServeProcess {
+        child: child_proc,
+        storage: storage_dir,
+        port,
+        username: username.to_owned(),
+        password: password.to_owned(),
+        executable: executable.to_owned(),
+        limits,
+        tls: tls.is_some(),
+    });
+
+    discovery::write_pidfile(pid, port as u16, tls.is_some());
 
     window
         .emit("database:start", true)
         .expect("start result should be delivered");
 
+    if let Some(seed) = seed {
+        seed::apply_seed(window.clone(), pid, seed);
+    }
+
+    let query_log = window.state::<querylog::QueryLogState>();
+
+    query_log.0.lock().unwrap().reset();
+
     thread::spawn(move || {
         let reader = BufReader::new(output);
         let mut has_started = false;
+        let mut recent = VecDeque::new();
 
         for line in reader.lines() {
             let message = line.unwrap();
 
             println!("Surreal: {}", message);
 
+            if recent.len() >= 20 {
+                recent.pop_front();
+            }
+
+            recent.push_back(message.clone());
+
+            window
+                .state::<querylog::QueryLogState>()
+                .0
+                .lock()
+                .unwrap()
+                .ingest(&message);
+
             window
                 .emit("database:output", message)
                 .expect("console output should be delivered");
@@ -85,9 +258,47 @@ pub fn start_database(
             has_started = true;
         }
 
+        discovery::remove_pidfile(pid);
+
+        // The process is only still present when it exited on its own
+        let exited = {
+            let handle = window.app_handle();
+            let state = handle.state::<DatabaseState>();
+            let mut process = state.0.lock().unwrap();
+
+            match process.take() {
+                Some(mut proc) if proc.child.id() == pid => {
+                    let status = proc.child.wait().ok();
+
+                    Some((proc.limits, status))
+                }
+                other => {
+                    *process = other;
+                    None
+                }
+            }
+        };
+
+        // The playground only serves this process, so it stops along with it
+        if exited.is_some() {
+            tikv::stop_playground(window.app_handle());
+        }
+
+        let violation = exited.and_then(|(limits, status)| {
+            limits.detect_violation(status?, recent.make_contiguous())
+        });
+
         let elapsed = start_at.elapsed().as_millis();
 
-        if elapsed <= 500 {
+        if let Some(violation) = violation {
+            window
+                .emit("database:limit", violation)
+                .expect("limit result should be delivered");
+
+            window
+                .emit("database:stop", true)
+                .expect("stop result should be delivered");
+        } else if elapsed <= 500 {
             if !has_started {
                 window.emit("database:output", "SurrealDB did not start. Are you sure the Surreal executable is available?").expect("console output should be delivered");
             }
@@ -103,27 +314,37 @@ pub fn start_database(
                 .emit("database:stop", true)
                 .expect("stop result should be delivered");
         }
-
-        let handle = window.app_handle();
-        let state = handle.state::<DatabaseState>();
-        let mut process = state.0.lock().unwrap();
-
-        *process = None;
     });
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn stop_database(state: tauri::State<DatabaseState>) -> Result<bool, String> {
+pub fn stop_database(
+    app: tauri::AppHandle,
+    state: tauri::State<DatabaseState>,
+) -> Result<bool, String> {
+    let stopped = stop_serve_process(&state);
+
+    tikv::stop_playground(&app);
+
+    Ok(stopped)
+}
+
+///
+/// Stop the running serve process and wait for it to exit
+///
+pub fn stop_serve_process(state: &DatabaseState) -> bool {
     let process = state.0.lock().unwrap().take();
 
     match process {
-        None => Ok(false),
-        Some(child) => {
-            kill_surreal_process(child.id());
+        None => false,
+        Some(mut process) => {
+            kill_surreal_process(process.child.id());
 
-            Ok(true)
+            let _ = process.child.wait();
+
+            true
         }
     }
 }
@@ -143,7 +364,42 @@ pub fn kill_surreal_process(id: u32) {
         .expect("surreal process should be killed");
 }
 
+///
+/// Spawn a surreal CLI subcommand through the user's shell with the given
+/// environment, with stdout and stderr piped back to the caller
+///
+pub fn spawn_surreal_command(
+    executable: &str,
+    args: &[String],
+    env: &[(&str, String)],
+) -> std::io::Result<Child> {
+    let path = if executable.is_empty() {
+        "surreal"
+    } else {
+        executable
+    };
+
+    let quoted: Vec<String> = std::iter::once(path)
+        .chain(args.iter().map(String::as_str))
+        .map(shell::quote_argument)
+        .collect();
+
+    let shell_cmd = shell::build_shell_command(quoted.iter().map(String::as_str).collect());
+    let mut cmd_chain = Command::new(&shell_cmd[0]);
+
+    shell::configure_command(&mut cmd_chain);
+
+    cmd_chain
+        .args(&shell_cmd[1..])
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
 pub struct ServeOptions<'s> {
+    host: &'s str,
     username: &'s str,
     password: &'s str,
     port: u32,
@@ -152,13 +408,19 @@ pub struct ServeOptions<'s> {
     executable: &'s str,
     log_level: &'s str,
     legacy_compat: bool,
+    limits: &'s ServeLimits,
+    telemetry: Option<&'s str>,
+    tls: Option<&'s tls::TlsFiles>,
+    create_storage: bool,
+    /// Kill stray surreal processes first in development builds on Windows
+    exclusive: bool,
 }
 
 ///
 /// Start a new SurrealDB process and return the child process
 ///
 pub fn start_surreal_process(options: ServeOptions) -> Result<Child, String> {
-    let bind_addr = format!("0.0.0.0:{}", options.port);
+    let bind_addr = format!("{}:{}", options.host, options.port);
     let path = if options.executable.is_empty() {
         "surreal"
     } else {
@@ -182,34 +444,66 @@ pub fn start_surreal_process(options: ServeOptions) -> Result<Child, String> {
         args.push("--auth")
     }
 
-    let file_uri = format!("rocksdb://{}", options.storage);
-    let surrealkv_uri = format!("surrealkv://{}", options.storage);
-    let tikv_uri = format!("tikv://{}", options.storage);
+    let storage_uri =
+        storage::build_storage_uri(options.driver, options.storage, options.create_storage)?;
+
+    let certificate = options
+        .tls
+        .map(|t| t.certificate.to_string_lossy().into_owned());
+    let key = options.tls.map(|t| t.key.to_string_lossy().into_owned());
 
-    match options.driver {
-        "memory" => args.push("memory"),
-        "file" => args.push(&file_uri),
-        "surrealkv" => args.push(&surrealkv_uri),
-        "tikv" => args.push(&tikv_uri),
-        _ => Err("Invalid database driver")?,
+    if let (Some(certificate), Some(key)) = (&certificate, &key) {
+        args.extend(["--web-crt", certificate, "--web-key", key]);
     }
 
+    args.push(&storage_uri);
     args.push("--allow-all");
 
-    println!("Launching with: {:?}", args);
+    let printable: Vec<&str> = args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            if i > 0 && args[i - 1] == "--pass" {
+                "********"
+            } else {
+                arg
+            }
+        })
+        .collect();
 
-    let shell_cmd = shell::build_start_command(args);
+    println!("Launching with: {:?}", printable);
+
+    // The prefix is shell syntax, while every argument is passed literally
+    let prefix = options.limits.command_prefix()?;
+    let quoted: Vec<String> = args.into_iter().map(shell::quote_argument).collect();
+    let command = prefix.iter().chain(&quoted).map(String::as_str).collect();
+    let shell_cmd = if options.exclusive {
+        shell::build_start_command(command)
+    } else {
+        shell::build_shell_command(command)
+    };
     let mut cmd_chain = Command::new(&shell_cmd[0]);
 
     shell::configure_command(&mut cmd_chain);
 
+    if let Some(dir) = &options.limits.working_directory {
+        cmd_chain.current_dir(dir);
+    }
+
+    if let Some(endpoint) = options.telemetry {
+        cmd_chain
+            .env("SURREAL_TELEMETRY_PROVIDER", "otlp")
+            .env("OTEL_EXPORTER_OTLP_ENDPOINT", endpoint)
+            .env("OTEL_EXPORTER_OTLP_PROTOCOL", "grpc");
+    }
+
     let child_proc = cmd_chain
         .args(&shell_cmd[1..])
         .stdout(Stdio::null())
         .stderr(Stdio::piped())
         .env("SURREAL_EXPERIMENTAL_GRAPHQL", "true")
         .spawn()
-        .expect("surreal process should be spawned");
+        .map_err(|e| format!("Failed to launch surreal: {}", e))?;
 
     Ok(child_proc)
 }
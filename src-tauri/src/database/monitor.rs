@@ -0,0 +1,235 @@
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::Mutex,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Disks, Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::{files, DatabaseState};
+
+/// The sampling interval and warning thresholds of the resource monitor
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorSettings {
+    pub interval_ms: u64,
+    pub history_size: usize,
+    pub memory_warning: Option<u64>,
+    pub disk_warning: Option<u64>,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            interval_ms: 2000,
+            history_size: 300,
+            memory_warning: None,
+            disk_warning: None,
+        }
+    }
+}
+
+/// A single resource usage sample of the serve process tree
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStats {
+    pub timestamp: u64,
+    pub cpu: f32,
+    pub memory: u64,
+    pub open_files: Option<u64>,
+    pub storage_size: Option<u64>,
+    pub disk_free: Option<u64>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceWarning {
+    kind: &'static str,
+    message: String,
+}
+
+/// The state of the resource monitor
+#[derive(Default)]
+pub struct MonitorState {
+    pub settings: Mutex<MonitorSettings>,
+    pub history: Mutex<VecDeque<ProcessStats>>,
+}
+
+/// Start the background thread sampling the serve process
+pub fn start_resource_monitor(app: AppHandle) {
+    thread::spawn(move || {
+        let mut system = System::new();
+        let mut last_pid = None;
+        let mut memory_warned = false;
+        let mut disk_warned = false;
+
+        loop {
+            let state = app.state::<MonitorState>();
+            let settings = state.settings.lock().unwrap().clone();
+
+            thread::sleep(Duration::from_millis(settings.interval_ms.max(250)));
+
+            let process = {
+                let database = app.state::<DatabaseState>();
+                let database = database.0.lock().unwrap();
+
+                database.as_ref().map(|p| (p.child.id(), p.storage.clone()))
+            };
+
+            let Some((pid, storage)) = process else {
+                last_pid = None;
+                continue;
+            };
+
+            if last_pid != Some(pid) {
+                state.history.lock().unwrap().clear();
+                last_pid = Some(pid);
+                memory_warned = false;
+                disk_warned = false;
+            }
+
+            let stats = sample_process(&mut system, pid, storage.as_deref());
+
+            if let Some(limit) = settings.memory_warning {
+                let exceeded = stats.memory >= limit;
+
+                if exceeded && !memory_warned {
+                    emit_warning(
+                        &app,
+                        "memory",
+                        format!("SurrealDB is using {} bytes of memory", stats.memory),
+                    );
+                }
+
+                memory_warned = exceeded;
+            }
+
+            if let (Some(limit), Some(free)) = (settings.disk_warning, stats.disk_free) {
+                let exceeded = free <= limit;
+
+                if exceeded && !disk_warned {
+                    emit_warning(
+                        &app,
+                        "disk",
+                        format!("Only {} bytes of disk space remain for storage", free),
+                    );
+                }
+
+                disk_warned = exceeded;
+            }
+
+            {
+                let mut history = state.history.lock().unwrap();
+
+                history.push_back(stats.clone());
+
+                while history.len() > settings.history_size.max(1) {
+                    history.pop_front();
+                }
+            }
+
+            app.emit("database:stats", stats)
+                .expect("stats should be delivered");
+        }
+    });
+}
+
+fn emit_warning(app: &AppHandle, kind: &'static str, message: String) {
+    warn!("{}", message);
+
+    app.emit("database:warning", ResourceWarning { kind, message })
+        .expect("warning should be delivered");
+}
+
+/// Sample the resource usage of a process and all of its descendants
+fn sample_process(system: &mut System, pid: u32, storage: Option<&Path>) -> ProcessStats {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+
+    let mut tree = vec![Pid::from_u32(pid)];
+    let mut index = 0;
+
+    while index < tree.len() {
+        let parent = tree[index];
+
+        tree.extend(
+            system
+                .processes()
+                .iter()
+                .filter(|(_, p)| p.parent() == Some(parent))
+                .map(|(pid, _)| *pid),
+        );
+
+        index += 1;
+    }
+
+    let processes: Vec<_> = tree.iter().filter_map(|p| system.process(*p)).collect();
+
+    ProcessStats {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64,
+        cpu: processes.iter().map(|p| p.cpu_usage()).sum(),
+        memory: processes.iter().map(|p| p.memory()).sum(),
+        open_files: count_open_files(&tree),
+        storage_size: storage.map(files::directory_size),
+        disk_free: storage.and_then(free_disk_space),
+    }
+}
+
+/// Find the free space on the disk holding the given path
+fn free_disk_space(path: &Path) -> Option<u64> {
+    let path = path.canonicalize().ok()?;
+    let disks = Disks::new_with_refreshed_list();
+
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
+#[cfg(target_os = "linux")]
+fn count_open_files(tree: &[Pid]) -> Option<u64> {
+    let count = tree
+        .iter()
+        .filter_map(|pid| std::fs::read_dir(format!("/proc/{}/fd", pid)).ok())
+        .map(|entries| entries.count() as u64)
+        .sum();
+
+    Some(count)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn count_open_files(_tree: &[Pid]) -> Option<u64> {
+    None
+}
+
+#[tauri::command]
+pub fn configure_monitor(state: State<MonitorState>, settings: MonitorSettings) {
+    *state.settings.lock().unwrap() = settings;
+}
+
+#[tauri::command]
+pub fn get_database_stats(state: State<MonitorState>, since: Option<u64>) -> Vec<ProcessStats> {
+    let since = since.unwrap_or_default();
+
+    state
+        .history
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|s| s.timestamp > since)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,341 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use tauri::State;
+
+const MAX_SLOW_QUERIES: usize = 1000;
+
+/// A request which took longer than the configured threshold. Requests with
+/// several statements are listed as a whole, as SurrealDB only logs the
+/// timing of the entire request.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQuery {
+    pub query: String,
+    pub duration_ms: f64,
+    pub namespace: Option<String>,
+    pub database: Option<String>,
+    pub error: bool,
+    pub timestamp: u64,
+}
+
+/// Aggregated statistics of a normalized statement. Durations only cover
+/// executions in a request of their own for which the server reported a
+/// timing.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStats {
+    pub query: String,
+    pub count: u64,
+    pub errors: u64,
+    pub timed: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+struct PendingQuery {
+    query: String,
+    namespace: Option<String>,
+    database: Option<String>,
+}
+
+/// The queries extracted from the log output of the serve process
+pub struct QueryLog {
+    threshold: Duration,
+    namespace: Option<String>,
+    database: Option<String>,
+    pending: Vec<PendingQuery>,
+    slow: Vec<SlowQuery>,
+    stats: HashMap<String, QueryStats>,
+}
+
+pub struct QueryLogState(pub Mutex<QueryLog>);
+
+impl Default for QueryLog {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_millis(100),
+            namespace: None,
+            database: None,
+            pending: Vec::new(),
+            slow: Vec::new(),
+            stats: HashMap::new(),
+        }
+    }
+}
+
+impl QueryLog {
+    /// Forget all queries of the previous session
+    pub fn reset(&mut self) {
+        self.namespace = None;
+        self.database = None;
+        self.pending.clear();
+        self.slow.clear();
+        self.stats.clear();
+    }
+
+    ///
+    /// Process a single line of serve output. Statements are picked up from
+    /// the `Executing:` lines logged at debug and trace level, and completed
+    /// by the request span finishing or the next statement starting. Only
+    /// the timings reported by SurrealDB are used as durations.
+    ///
+    pub fn ingest(&mut self, line: &str) {
+        let line = strip_ansi(line);
+        let statement = line.find("Executing: ");
+
+        // Only the structured fields preceding the statement are considered,
+        // as the statement itself may contain anything
+        let fields = &line[..statement.unwrap_or(line.len())];
+
+        if let Some(ns) = field_value(fields, &["ns", "namespace"]) {
+            self.namespace = Some(ns);
+        }
+
+        if let Some(db) = field_value(fields, &["db", "database"]) {
+            self.database = Some(db);
+        }
+
+        if let Some(index) = statement {
+            self.complete(None, false);
+
+            self.pending.push(PendingQuery {
+                query: line[index + 11..].trim().to_owned(),
+                namespace: self.namespace.clone(),
+                database: self.database.clone(),
+            });
+
+            return;
+        }
+
+        let finished = line.contains("finished processing request")
+            || line.contains("Process RPC response")
+            || line.contains("close time.busy");
+
+        if finished && !self.pending.is_empty() {
+            let latency = field_value(&line, &["http.latency.ms", "latency_ms", "elapsed_ms"])
+                .and_then(|v| v.trim_end_matches("ms").parse::<f64>().ok())
+                .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+                .or_else(|| field_value(&line, &["time.busy"]).and_then(|v| parse_span_time(&v)));
+
+            let status = field_value(&line, &["http.response.status_code", "status"]);
+            let error = line.contains(" ERROR ")
+                || line.contains("otel.status_code=\"ERROR\"")
+                || status.is_some_and(|s| s.starts_with('4') || s.starts_with('5'));
+
+            self.complete(latency, error);
+        }
+    }
+
+    /// Record all pending statements as completed by a single request
+    fn complete(&mut self, latency: Option<Duration>, error: bool) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let pending = std::mem::take(&mut self.pending);
+        let duration = latency.map(|l| l.as_secs_f64() * 1000.0);
+
+        // The latency belongs to the request, so it is only attributed to a
+        // statement when the request contained nothing else
+        let statement_duration = duration.filter(|_| pending.len() == 1);
+
+        if let (Some(first), Some(duration)) = (pending.first(), duration) {
+            if duration >= self.threshold.as_secs_f64() * 1000.0 {
+                let query = pending
+                    .iter()
+                    .map(|p| p.query.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                if self.slow.len() >= MAX_SLOW_QUERIES {
+                    self.slow.remove(0);
+                }
+
+                self.slow.push(SlowQuery {
+                    query,
+                    duration_ms: duration,
+                    namespace: first.namespace.clone(),
+                    database: first.database.clone(),
+                    error,
+                    timestamp,
+                });
+            }
+        }
+
+        for pending in pending {
+            let key = normalize_query(&pending.query);
+            let stats = self.stats.entry(key.clone()).or_insert(QueryStats {
+                query: key,
+                count: 0,
+                errors: 0,
+                timed: 0,
+                total_ms: 0.0,
+                max_ms: 0.0,
+            });
+
+            stats.count += 1;
+
+            if error {
+                stats.errors += 1;
+            }
+
+            if let Some(duration) = statement_duration {
+                stats.timed += 1;
+                stats.total_ms += duration;
+                stats.max_ms = stats.max_ms.max(duration);
+            }
+        }
+    }
+}
+
+/// Parse a span timing such as `1.52ms` or `830µs`
+fn parse_span_time(value: &str) -> Option<Duration> {
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (amount, unit) = value.split_at(split);
+    let amount: f64 = amount.parse().ok()?;
+
+    let seconds = match unit {
+        "ns" => amount / 1e9,
+        "µs" | "us" => amount / 1e6,
+        "ms" => amount / 1e3,
+        "s" => amount,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs_f64(seconds))
+}
+
+/// Remove terminal color codes from a log line
+fn strip_ansi(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Extract the value of a `key=value` field from a structured log line
+fn field_value(line: &str, keys: &[&str]) -> Option<String> {
+    for key in keys {
+        let pattern = format!("{}=", key);
+        let mut search = 0;
+
+        while let Some(found) = line[search..].find(&pattern) {
+            let start = search + found;
+            let boundary = start == 0
+                || !line[..start].ends_with(|c: char| c.is_alphanumeric() || c == '.' || c == '_');
+
+            search = start + pattern.len();
+
+            if !boundary {
+                continue;
+            }
+
+            let rest = &line[search..];
+            let value = match rest.strip_prefix('"') {
+                Some(quoted) => quoted.split('"').next().unwrap_or_default(),
+                None => rest.split([' ', ',', '}']).next().unwrap_or_default(),
+            };
+
+            if !value.is_empty() {
+                return Some(value.to_owned());
+            }
+        }
+    }
+
+    None
+}
+
+/// Replace literals in a statement so similar statements are grouped
+fn normalize_query(query: &str) -> String {
+    let mut result = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut last_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                for next in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+
+                result.push('?');
+                last_space = false;
+            }
+            c if c.is_ascii_digit()
+                && !result.ends_with(|p: char| p.is_alphanumeric() || p == '_' || p == ':') =>
+            {
+                while chars
+                    .peek()
+                    .is_some_and(|n| n.is_ascii_digit() || *n == '.')
+                {
+                    chars.next();
+                }
+
+                result.push('?');
+                last_space = false;
+            }
+            c if c.is_whitespace() => {
+                if !last_space {
+                    result.push(' ');
+                }
+
+                last_space = true;
+            }
+            c => {
+                result.push(c);
+                last_space = false;
+            }
+        }
+    }
+
+    result.trim().to_owned()
+}
+
+#[tauri::command]
+pub fn configure_query_log(state: State<QueryLogState>, threshold_ms: u64) {
+    state.0.lock().unwrap().threshold = Duration::from_millis(threshold_ms);
+}
+
+#[tauri::command]
+pub fn list_slow_queries(state: State<QueryLogState>, limit: Option<usize>) -> Vec<SlowQuery> {
+    let mut slow = state.0.lock().unwrap().slow.clone();
+
+    slow.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+    slow.truncate(limit.unwrap_or(100));
+    slow
+}
+
+#[tauri::command]
+pub fn list_frequent_queries(state: State<QueryLogState>, limit: Option<usize>) -> Vec<QueryStats> {
+    let mut stats: Vec<QueryStats> = state.0.lock().unwrap().stats.values().cloned().collect();
+
+    stats.sort_by_key(|s| Reverse(s.count));
+    stats.truncate(limit.unwrap_or(100));
+    stats
+}
+
+#[tauri::command]
+pub fn clear_query_log(state: State<QueryLogState>) {
+    state.0.lock().unwrap().reset();
+}
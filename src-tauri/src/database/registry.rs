@@ -0,0 +1,340 @@
+use std::{
+    fs::{self, read_to_string},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{files, storage, DatabaseState};
+use crate::{
+    config::write_atomic,
+    paths::{get_local_databases_directory, get_local_databases_path},
+};
+
+/// Serializes modifications of the local database registry
+#[derive(Default)]
+pub struct RegistryState(pub Mutex<()>);
+
+/// A local storage directory known to Surrealist
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDatabase {
+    pub id: String,
+    pub name: String,
+    pub engine: String,
+    pub path: String,
+    pub created_at: u64,
+    pub last_used: Option<u64>,
+    #[serde(default)]
+    pub branches: Vec<LocalBranch>,
+}
+
+/// A named snapshot of a local database storage directory
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalBranch {
+    pub name: String,
+    pub path: String,
+    pub created_at: u64,
+}
+
+/// A local database including its current disk usage
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalDatabaseInfo {
+    #[serde(flatten)]
+    pub database: LocalDatabase,
+    pub size: u64,
+    pub in_use: bool,
+}
+
+///
+/// Read the list of registered local databases. A registry which cannot be
+/// parsed is reported instead of treated as empty, so it is never replaced.
+///
+pub fn read_local_databases() -> Result<Vec<LocalDatabase>, String> {
+    let content = match read_to_string(get_local_databases_path()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read local database registry: {}", e)),
+    };
+
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Local database registry is corrupted: {}", e))
+}
+
+/// Write the list of registered local databases
+fn write_local_databases(list: &[LocalDatabase]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(list).map_err(|e| e.to_string())?;
+
+    write_atomic(&get_local_databases_path(), content.as_bytes())
+        .map_err(|_| "Failed to write local database registry".into())
+}
+
+///
+/// Modify the list of registered local databases while holding the registry
+/// lock, writing it back when the modification succeeds
+///
+pub fn update_local_databases<T>(
+    registry: &RegistryState,
+    update: impl FnOnce(&mut Vec<LocalDatabase>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = registry.0.lock().unwrap();
+    let mut list = read_local_databases()?;
+    let result = update(&mut list)?;
+
+    write_local_databases(&list)?;
+
+    Ok(result)
+}
+
+/// Record that the local database stored at the given path was just used
+pub fn touch_local_database(registry: &RegistryState, path: &Path) {
+    let result = update_local_databases(registry, |list| {
+        for db in list.iter_mut() {
+            let is_used = files::same_path(Path::new(&db.path), path)
+                || db
+                    .branches
+                    .iter()
+                    .any(|b| files::same_path(Path::new(&b.path), path));
+
+            if is_used {
+                db.last_used = Some(now_millis());
+            }
+        }
+
+        Ok(())
+    });
+
+    if let Err(err) = result {
+        warn!("Failed to record local database usage: {}", err);
+    }
+}
+
+/// Returns whether the running serve process uses the given storage directory
+pub fn is_storage_in_use(state: &DatabaseState, path: &Path) -> bool {
+    state
+        .0
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|p| p.uses_storage(path))
+}
+
+/// Look up a registered local database by its id
+pub fn find_local_database(id: &str) -> Result<LocalDatabase, String> {
+    read_local_databases()?
+        .into_iter()
+        .find(|db| db.id == id)
+        .ok_or_else(|| "Local database does not exist".into())
+}
+
+/// Allocate a new unused directory for a managed local database
+pub fn allocate_directory(name: &str) -> PathBuf {
+    files::unique_directory(&get_local_databases_directory(), name)
+}
+
+/// The current unix time in milliseconds
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+fn generate_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+
+    format!("{:x}", nanos)
+}
+
+fn check_engine(engine: &str) -> Result<(), String> {
+    match engine {
+        "file" | "surrealkv" | "surrealkv+versioned" => Ok(()),
+        _ => Err(format!("Unsupported local database engine: {}", engine)),
+    }
+}
+
+#[tauri::command]
+pub fn list_local_databases(
+    state: tauri::State<DatabaseState>,
+) -> Result<Vec<LocalDatabaseInfo>, String> {
+    let list = read_local_databases()?
+        .into_iter()
+        .map(|database| {
+            let path = PathBuf::from(&database.path);
+
+            LocalDatabaseInfo {
+                size: files::directory_size(&path),
+                in_use: is_storage_in_use(&state, &path),
+                database,
+            }
+        })
+        .collect();
+
+    Ok(list)
+}
+
+#[tauri::command]
+pub fn create_local_database(
+    registry: tauri::State<RegistryState>,
+    name: &str,
+    engine: &str,
+    path: Option<String>,
+) -> Result<LocalDatabase, String> {
+    check_engine(engine)?;
+
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("Local database name cannot be empty".into());
+    }
+
+    update_local_databases(&registry, |list| {
+        let path = match path {
+            Some(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+            _ => allocate_directory(name),
+        };
+
+        if list
+            .iter()
+            .any(|db| files::same_path(Path::new(&db.path), &path))
+        {
+            return Err("This directory is already registered as a local database".into());
+        }
+
+        let path_str = path.to_string_lossy().into_owned();
+
+        storage::build_storage_uri(engine, &path_str, true)?;
+
+        let database = LocalDatabase {
+            id: generate_id(),
+            name: name.to_owned(),
+            engine: engine.to_owned(),
+            path: path_str,
+            created_at: now_millis(),
+            last_used: None,
+            branches: Vec::new(),
+        };
+
+        info!("Created local database {}", database.name);
+
+        list.push(database.clone());
+
+        Ok(database)
+    })
+}
+
+#[tauri::command]
+pub fn rename_local_database(
+    registry: tauri::State<RegistryState>,
+    id: &str,
+    name: &str,
+) -> Result<(), String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("Local database name cannot be empty".into());
+    }
+
+    update_local_databases(&registry, |list| {
+        let database = list
+            .iter_mut()
+            .find(|db| db.id == id)
+            .ok_or("Local database does not exist")?;
+
+        database.name = name.to_owned();
+
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn duplicate_local_database(
+    state: tauri::State<DatabaseState>,
+    registry: tauri::State<RegistryState>,
+    id: &str,
+    name: &str,
+) -> Result<LocalDatabase, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("Local database name cannot be empty".into());
+    }
+
+    let source = find_local_database(id)?;
+    let source_path = PathBuf::from(&source.path);
+
+    if is_storage_in_use(&state, &source_path) {
+        return Err("Stop the database before duplicating it".into());
+    }
+
+    let target_path = allocate_directory(name);
+
+    files::clone_directory(&source_path, &target_path)
+        .map_err(|e| format!("Failed to copy local database: {}", e))?;
+
+    let database = LocalDatabase {
+        id: generate_id(),
+        name: name.to_owned(),
+        engine: source.engine,
+        path: target_path.to_string_lossy().into_owned(),
+        created_at: now_millis(),
+        last_used: None,
+        branches: Vec::new(),
+    };
+
+    update_local_databases(&registry, |list| {
+        list.push(database.clone());
+
+        Ok(database)
+    })
+}
+
+#[tauri::command]
+pub fn delete_local_database(
+    state: tauri::State<DatabaseState>,
+    registry: tauri::State<RegistryState>,
+    id: &str,
+    keep_files: bool,
+) -> Result<(), String> {
+    let database = find_local_database(id)?;
+    let path = PathBuf::from(&database.path);
+
+    // Hold the process lock until the files are gone so no serve can start
+    // on the directory while it is being deleted
+    let process = state.0.lock().unwrap();
+    let in_use = |path: &Path| process.as_ref().is_some_and(|p| p.uses_storage(path));
+
+    let branch_in_use = database.branches.iter().any(|b| in_use(Path::new(&b.path)));
+
+    if branch_in_use || in_use(&path) {
+        return Err("Cannot delete a local database while it is being served".into());
+    }
+
+    if !keep_files && path.exists() {
+        fs::remove_dir_all(&path)
+            .map_err(|e| format!("Failed to delete local database files: {}", e))?;
+
+        for branch in &database.branches {
+            let _ = fs::remove_dir_all(&branch.path);
+        }
+    }
+
+    drop(process);
+
+    info!("Deleted local database {}", database.name);
+
+    update_local_databases(&registry, |list| {
+        list.retain(|db| db.id != id);
+
+        Ok(())
+    })
+}
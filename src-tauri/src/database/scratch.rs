@@ -0,0 +1,194 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    process::Child,
+    sync::Mutex,
+    thread,
+};
+
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use log::info;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use super::{
+    client::wait_until_ready, discovery, kill_surreal_process, limits::ServeLimits,
+    start_surreal_process, ServeOptions,
+};
+
+/// A throwaway in-memory database owned by a single window
+pub struct ScratchInstance {
+    pub child: Child,
+    pub connection: ScratchConnection,
+}
+
+/// The connection details of a scratch database
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScratchConnection {
+    pub endpoint: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+}
+
+/// The state holding scratch databases keyed by the label of their window
+pub struct ScratchState(pub Mutex<HashMap<String, ScratchInstance>>);
+
+/// Generate a random hexadecimal password using the OS random generator
+fn random_hex() -> String {
+    let mut bytes = [0u8; 16];
+
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Kill a scratch database and wait for it to exit
+fn destroy_instance(mut instance: ScratchInstance) {
+    kill_surreal_process(instance.child.id());
+
+    let _ = instance.child.wait();
+}
+
+///
+/// Stop the scratch database owned by the given window, if any
+///
+pub fn release_window(app: &AppHandle, label: &str) {
+    let instance = app.state::<ScratchState>().0.lock().unwrap().remove(label);
+
+    if let Some(instance) = instance {
+        info!("Destroying scratch database of window {}", label);
+
+        destroy_instance(instance);
+    }
+}
+
+///
+/// Stop all scratch databases
+///
+pub fn release_all(app: &AppHandle) {
+    let instances: Vec<_> = app
+        .state::<ScratchState>()
+        .0
+        .lock()
+        .unwrap()
+        .drain()
+        .collect();
+
+    for (_, instance) in instances {
+        destroy_instance(instance);
+    }
+}
+
+#[tauri::command]
+pub fn start_scratch_database(
+    window: Window,
+    state: State<ScratchState>,
+    executable: &str,
+) -> Result<ScratchConnection, String> {
+    let label = window.label().to_owned();
+    let mut instances = state.0.lock().unwrap();
+
+    if let Some(instance) = instances.get(&label) {
+        return Ok(instance.connection.clone());
+    }
+
+    let port = portpicker::pick_unused_port().ok_or("No free port available")?;
+    let password = random_hex();
+    let connection = ScratchConnection {
+        endpoint: format!("http://127.0.0.1:{}", port),
+        port,
+        username: "root".to_owned(),
+        password,
+    };
+
+    info!("Starting scratch database for window {}", label);
+
+    let limits = ServeLimits::default();
+    let mut child = start_surreal_process(ServeOptions {
+        host: "127.0.0.1",
+        username: &connection.username,
+        password: &connection.password,
+        port: port as u32,
+        driver: "memory",
+        storage: "",
+        executable,
+        log_level: "info",
+        legacy_compat: false,
+        limits: &limits,
+        telemetry: None,
+        tls: None,
+        create_storage: false,
+        exclusive: false,
+    })?;
+
+    let pid = child.id();
+    let output = child.stderr.take().unwrap();
+
+    discovery::write_pidfile(pid, port, false);
+
+    instances.insert(
+        label.clone(),
+        ScratchInstance {
+            child,
+            connection: connection.clone(),
+        },
+    );
+
+    let endpoint = connection.endpoint.clone();
+    let ready_window = window.clone();
+
+    thread::spawn(move || {
+        let handle = ready_window.app_handle().clone();
+        let is_running = || {
+            let state = handle.state::<ScratchState>();
+            let instances = state.0.lock().unwrap();
+
+            instances
+                .get(ready_window.label())
+                .is_some_and(|i| i.child.id() == pid)
+        };
+
+        if wait_until_ready(&endpoint, is_running) {
+            ready_window
+                .emit_to(ready_window.label(), "scratch:ready", true)
+                .expect("ready result should be delivered");
+        }
+    });
+
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            window
+                .emit_to(&label, "scratch:output", line)
+                .expect("console output should be delivered");
+        }
+
+        discovery::remove_pidfile(pid);
+
+        let handle = window.app_handle();
+        let state = handle.state::<ScratchState>();
+        let exited = {
+            let mut instances = state.0.lock().unwrap();
+            let is_current = instances.get(&label).is_some_and(|i| i.child.id() == pid);
+
+            is_current.then(|| instances.remove(&label)).flatten()
+        };
+
+        // The process exited on its own, so only its exit status is collected
+        if let Some(mut instance) = exited {
+            let _ = instance.child.wait();
+        }
+
+        window
+            .emit_to(&label, "scratch:stop", true)
+            .expect("stop result should be delivered");
+    });
+
+    Ok(connection)
+}
+
+#[tauri::command]
+pub fn stop_scratch_database(app: AppHandle, window: Window) {
+    release_window(&app, window.label());
+}
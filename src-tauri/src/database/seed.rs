@@ -0,0 +1,402 @@
+use std::{fs::read_to_string, thread};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{Emitter, Manager, Window};
+
+use super::{
+    client::{execute_sql, wait_until_ready, QueryResponse, SqlTarget},
+    DatabaseState,
+};
+use crate::whitelist::read_allowed_files;
+
+/// Seed scripts applied once the serve process is ready
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedOptions {
+    pub files: Vec<String>,
+    pub namespace: String,
+    pub database: String,
+}
+
+/// A single statement within a script, with its 1-based location
+pub struct Statement {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct SeedResult {
+    file: String,
+    line: usize,
+    column: usize,
+    success: bool,
+    result: Value,
+}
+
+#[derive(Clone, Serialize)]
+struct SeedError {
+    file: String,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl SeedOptions {
+    /// Ensure all seed files were previously allowed by the user
+    pub fn validate(&self) -> Result<(), String> {
+        let whitelist = read_allowed_files();
+
+        match self.files.iter().find(|f| !whitelist.contains(f)) {
+            Some(file) => Err(format!("Seed file {} is not allowed", file)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Transaction statements do not produce a result of their own
+const TRANSACTION_KEYWORDS: [&str; 3] = ["BEGIN", "COMMIT", "CANCEL"];
+
+/// The error reported for statements skipped because their transaction failed
+const CANCELLED_TRANSACTION: &str = "not executed due to a failed transaction";
+
+impl Statement {
+    /// Returns whether the server responds with a result for this statement
+    fn has_result(&self) -> bool {
+        let keyword = self
+            .text
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(';')
+            .to_ascii_uppercase();
+
+        !TRANSACTION_KEYWORDS.contains(&keyword.as_str())
+    }
+}
+
+///
+/// Split a SurrealQL script into its top level statements, used to locate
+/// the statement each result of the script belongs to
+///
+pub fn split_statements(script: &str) -> Vec<Statement> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let mut consumed = 1;
+
+        let is_comment =
+            c == '#' || (c == '-' && next == Some('-')) || (c == '/' && next == Some('/'));
+
+        if is_comment {
+            while i + consumed < chars.len() && chars[i + consumed] != '\n' {
+                consumed += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            consumed = 2;
+
+            while i + consumed < chars.len()
+                && !(chars[i + consumed - 1] == '*' && chars[i + consumed] == '/')
+            {
+                consumed += 1;
+            }
+
+            consumed = (consumed + 1).min(chars.len() - i);
+        } else {
+            if start.is_none() && !c.is_whitespace() && c != ';' {
+                start = Some((line, column));
+            }
+
+            match c {
+                '\'' | '"' | '`' | '⟨' => {
+                    let close = if c == '⟨' { '⟩' } else { c };
+
+                    while i + consumed < chars.len() && chars[i + consumed] != close {
+                        if chars[i + consumed] == '\\' {
+                            consumed += 1;
+                        }
+
+                        consumed += 1;
+                    }
+
+                    consumed = (consumed + 1).min(chars.len() - i);
+                }
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            if start.is_some() {
+                current.extend(&chars[i..i + consumed]);
+            }
+        }
+
+        for ch in &chars[i..i + consumed] {
+            if *ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        i += consumed;
+
+        let at_end = i >= chars.len();
+        let at_separator = c == ';' && depth == 0;
+
+        if (at_separator || at_end) && start.is_some() {
+            let (line, column) = start.take().unwrap();
+
+            statements.push(Statement {
+                text: std::mem::take(&mut current).trim_end().to_owned(),
+                line,
+                column,
+            });
+        }
+    }
+
+    statements
+}
+
+///
+/// Wrap a script in a transaction so a failing statement leaves nothing
+/// behind. Scripts managing their own transactions are returned unchanged,
+/// as transactions cannot be nested.
+///
+fn transactional(script: &str, statements: &[Statement]) -> Option<String> {
+    if statements.iter().any(|s| !s.has_result()) {
+        return None;
+    }
+
+    let terminated = statements.last().is_none_or(|s| s.text.ends_with(';'));
+    let separator = if terminated { "" } else { ";" };
+
+    Some(format!(
+        "BEGIN TRANSACTION;\n{}\n{}COMMIT TRANSACTION;",
+        script, separator
+    ))
+}
+
+///
+/// Find the statement which failed. Within a failed transaction every other
+/// statement reports that it was not executed, so the first other error is
+/// the cause.
+///
+fn failed_statement(responses: &[QueryResponse]) -> Option<usize> {
+    let failed = |r: &&QueryResponse| !r.is_ok();
+
+    responses
+        .iter()
+        .position(|r| failed(&r) && !r.error().contains(CANCELLED_TRANSACTION))
+        .or_else(|| responses.iter().position(|r| failed(&r)))
+}
+
+///
+/// Apply the seed scripts in a background thread once the serve process
+/// accepts connections. Each script runs in a transaction and seeding stops
+/// at the first script with a failing statement.
+///
+pub fn apply_seed(window: Window, pid: u32, options: SeedOptions) {
+    thread::spawn(move || {
+        let handle = window.app_handle().clone();
+        let connection = {
+            let state = handle.state::<DatabaseState>();
+            let process = state.0.lock().unwrap();
+
+            process
+                .as_ref()
+                .map(|p| (p.endpoint(), p.username.clone(), p.password.clone()))
+        };
+
+        let Some((endpoint, username, password)) = connection else {
+            return;
+        };
+
+        let is_running = || {
+            let state = handle.state::<DatabaseState>();
+            let process = state.0.lock().unwrap();
+
+            process.as_ref().is_some_and(|p| p.child.id() == pid)
+        };
+
+        if !wait_until_ready(&endpoint, is_running) {
+            error!("Database did not become ready, skipping seed scripts");
+            return;
+        }
+
+        let target = SqlTarget {
+            endpoint: &endpoint,
+            username: &username,
+            password: &password,
+            namespace: Some(&options.namespace),
+            database: Some(&options.database),
+        };
+
+        let mut total = 0;
+
+        for file in &options.files {
+            info!("Applying seed script {}", file);
+
+            let script = match read_to_string(file) {
+                Ok(script) => script,
+                Err(err) => {
+                    emit_error(&window, file, 0, 0, format!("Failed to read file: {}", err));
+                    return;
+                }
+            };
+
+            // The script is executed as a whole so that parameters, USE
+            // statements and transactions apply across statements
+            let statements = split_statements(&script);
+            let query = transactional(&script, &statements).unwrap_or_else(|| script.clone());
+            let statements: Vec<Statement> = statements
+                .into_iter()
+                .filter(Statement::has_result)
+                .collect();
+
+            let response = tauri::async_runtime::block_on(execute_sql(&target, &query));
+            let responses = match response {
+                Ok(responses) => responses,
+                Err(err) => {
+                    let (line, column) = statements.first().map_or((1, 1), |s| (s.line, s.column));
+
+                    emit_error(&window, file, line, column, err);
+                    return;
+                }
+            };
+
+            let failed = failed_statement(&responses);
+
+            for (index, response) in responses.into_iter().enumerate() {
+                let success = response.is_ok();
+
+                // Statements skipped by the failed transaction are not reported
+                if failed.is_some_and(|failed| failed != index) && !success {
+                    continue;
+                }
+
+                let (line, column) = statements.get(index).map_or((0, 0), |s| (s.line, s.column));
+
+                window
+                    .emit(
+                        "database:seed",
+                        SeedResult {
+                            file: file.clone(),
+                            line,
+                            column,
+                            success,
+                            result: response.result.clone(),
+                        },
+                    )
+                    .expect("seed result should be delivered");
+
+                if !success {
+                    emit_error(&window, file, line, column, response.error());
+                    return;
+                }
+
+                total += 1;
+            }
+        }
+
+        info!("Applied {} seed statements", total);
+
+        window
+            .emit("database:seed-complete", total)
+            .expect("seed result should be delivered");
+    });
+}
+
+fn emit_error(window: &Window, file: &str, line: usize, column: usize, message: String) {
+    error!(
+        "Seed script {} failed at {}:{}: {}",
+        file, line, column, message
+    );
+
+    window
+        .emit(
+            "database:seed-error",
+            SeedError {
+                file: file.to_owned(),
+                line,
+                column,
+                message,
+            },
+        )
+        .expect("seed error should be delivered");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: &str, result: &str) -> QueryResponse {
+        QueryResponse {
+            status: status.to_owned(),
+            result: Value::String(result.to_owned()),
+        }
+    }
+
+    #[test]
+    fn splits_statements_with_locations() {
+        let statements = split_statements(
+            "CREATE a;\n-- note; here\nUPDATE b SET c = 'x;y' ;\n  SELECT * FROM { a: 1; };",
+        );
+        let located: Vec<_> = statements
+            .iter()
+            .map(|s| (s.text.as_str(), s.line, s.column))
+            .collect();
+
+        assert_eq!(
+            located,
+            vec![
+                ("CREATE a;", 1, 1),
+                ("UPDATE b SET c = 'x;y' ;", 3, 1),
+                ("SELECT * FROM { a: 1; };", 4, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn wraps_scripts_without_transactions() {
+        let script = "CREATE a;\nCREATE b";
+        let wrapped = transactional(script, &split_statements(script)).unwrap();
+
+        assert_eq!(
+            wrapped,
+            "BEGIN TRANSACTION;\nCREATE a;\nCREATE b\n;COMMIT TRANSACTION;"
+        );
+
+        let script = "BEGIN;\nCREATE a;\nCOMMIT;";
+
+        assert!(transactional(script, &split_statements(script)).is_none());
+    }
+
+    #[test]
+    fn locates_cause_of_failed_transaction() {
+        let cancelled = "The query was not executed due to a failed transaction";
+        let responses = [
+            response("ERR", cancelled),
+            response("ERR", "Table b already exists"),
+            response("ERR", cancelled),
+        ];
+
+        assert_eq!(failed_statement(&responses), Some(1));
+        assert_eq!(failed_statement(&[response("OK", "")]), None);
+        assert_eq!(
+            failed_statement(&[response("OK", ""), response("ERR", cancelled)]),
+            Some(1)
+        );
+    }
+}
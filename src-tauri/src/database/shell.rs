@@ -17,11 +17,23 @@ pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
             .expect("surreal process should be killed");
     }
 
+    build_shell_command(args)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
+    build_shell_command(args)
+}
+
+// ----- Shell command builder
+
+#[cfg(target_os = "windows")]
+pub fn build_shell_command(args: Vec<&str>) -> Vec<String> {
     vec!["cmd".to_owned(), "/c".to_owned(), args.join(" ")]
 }
 
 #[cfg(target_os = "macos")]
-pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
+pub fn build_shell_command(args: Vec<&str>) -> Vec<String> {
     vec![
         "zsh".to_owned(),
         "-l".to_owned(),
@@ -31,7 +43,7 @@ pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
 }
 
 #[cfg(target_os = "linux")]
-pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
+pub fn build_shell_command(args: Vec<&str>) -> Vec<String> {
     vec![
         "bash".to_owned(),
         "-l".to_owned(),
@@ -40,6 +52,22 @@ pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
     ]
 }
 
+// ----- Argument quoting
+
+#[cfg(target_os = "windows")]
+pub fn quote_argument(arg: &str) -> String {
+    if arg.is_empty() || arg.contains([' ', '\t', '&', '|', '<', '>', '^', '"']) {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else {
+        arg.to_owned()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn quote_argument(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
 // ----- Kill command builder
 
 #[cfg(target_os = "windows")]
@@ -63,6 +91,35 @@ pub fn build_kill_command(id: &u32) -> Vec<String> {
     vec!["kill".to_owned(), "-9".to_owned(), id.to_string()]
 }
 
+// ----- Copy command builder
+
+#[cfg(target_os = "windows")]
+pub fn build_copy_command(_from: &str, _to: &str) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+pub fn build_copy_command(from: &str, to: &str) -> Option<Vec<String>> {
+    Some(vec![
+        "cp".to_owned(),
+        "-c".to_owned(),
+        "-R".to_owned(),
+        from.to_owned(),
+        to.to_owned(),
+    ])
+}
+
+#[cfg(target_os = "linux")]
+pub fn build_copy_command(from: &str, to: &str) -> Option<Vec<String>> {
+    Some(vec![
+        "cp".to_owned(),
+        "-a".to_owned(),
+        "--reflink=auto".to_owned(),
+        from.to_owned(),
+        to.to_owned(),
+    ])
+}
+
 // ----- Command configuration
 
 #[cfg(target_os = "windows")]
@@ -0,0 +1,252 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+};
+
+/// The storage engine backing a local database directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageEngine {
+    RocksDb,
+    SurrealKv,
+}
+
+impl StorageEngine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorageEngine::RocksDb => "RocksDB",
+            StorageEngine::SurrealKv => "SurrealKV",
+        }
+    }
+}
+
+///
+/// Build the storage URI passed to `surreal start` for the given driver,
+/// validating the storage location before the process is spawned. A missing
+/// storage directory is only created when `create` is set.
+///
+pub fn build_storage_uri(driver: &str, storage: &str, create: bool) -> Result<String, String> {
+    let storage = storage.trim();
+
+    match driver {
+        "memory" => Ok("memory".to_owned()),
+        "file" => {
+            check_storage_directory(storage, StorageEngine::RocksDb, create)?;
+            Ok(format!("rocksdb://{}", storage))
+        }
+        "surrealkv" => {
+            check_storage_directory(storage, StorageEngine::SurrealKv, create)?;
+            Ok(format!("surrealkv://{}", storage))
+        }
+        "surrealkv+versioned" => {
+            check_storage_directory(storage, StorageEngine::SurrealKv, create)?;
+            Ok(format!("surrealkv+versioned://{}", storage))
+        }
+        "tikv" => {
+            let endpoints = parse_pd_endpoints(storage)?;
+            Ok(format!("tikv://{}", endpoints.join(",")))
+        }
+        _ => Err(format!("Invalid database driver: {}", driver)),
+    }
+}
+
+///
+/// Detect the engine of an existing store by inspecting the files it contains
+///
+pub fn detect_storage_engine(path: &Path) -> Option<StorageEngine> {
+    let entries = fs::read_dir(path).ok()?;
+    let names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+
+    let is_rocksdb = names.iter().any(|n| n == "CURRENT")
+        && names
+            .iter()
+            .any(|n| n == "IDENTITY" || n.starts_with("OPTIONS-") || n.starts_with("MANIFEST-"));
+
+    if is_rocksdb {
+        return Some(StorageEngine::RocksDb);
+    }
+
+    let is_surrealkv = names
+        .iter()
+        .any(|n| n == "clog" || n == "manifest" || n == "vlog" || n.ends_with(".clog"));
+
+    if is_surrealkv {
+        return Some(StorageEngine::SurrealKv);
+    }
+
+    None
+}
+
+///
+/// Returns whether the storage directory of a file based driver is missing
+///
+#[tauri::command]
+pub fn is_storage_missing(driver: &str, storage: &str) -> bool {
+    let storage = storage.trim();
+
+    matches!(driver, "file" | "surrealkv" | "surrealkv+versioned")
+        && !storage.is_empty()
+        && !Path::new(storage).exists()
+}
+
+/// Verify the storage directory can be used by the given engine
+fn check_storage_directory(
+    storage: &str,
+    engine: StorageEngine,
+    create: bool,
+) -> Result<(), String> {
+    if storage.is_empty() {
+        return Err("No storage path specified".into());
+    }
+
+    let path = Path::new(storage);
+
+    if path.exists() {
+        if !path.is_dir() {
+            return Err(format!("Storage path {} is not a directory", storage));
+        }
+    } else if !create {
+        return Err(format!("Storage directory {} does not exist", storage));
+    } else {
+        fs::create_dir_all(path)
+            .map_err(|e| format!("Storage directory {} could not be created: {}", storage, e))?;
+    }
+
+    let probe = path.join(".surrealist-write-probe");
+
+    match File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+        }
+        Err(_) => {
+            return Err(format!("Storage directory {} is not writable", storage));
+        }
+    }
+
+    match detect_storage_engine(path) {
+        Some(existing) if existing != engine => Err(format!(
+            "Storage directory {} contains a {} database and cannot be opened with {}",
+            storage,
+            existing.name(),
+            engine.name()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parse a comma separated list of TiKV placement driver addresses
+fn parse_pd_endpoints(storage: &str) -> Result<Vec<String>, String> {
+    let endpoints: Vec<String> = storage
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|s| s.trim().trim_start_matches("tikv://"))
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+
+    if endpoints.is_empty() {
+        return Err("No TiKV placement driver address specified".into());
+    }
+
+    for endpoint in &endpoints {
+        let valid = match endpoint.rsplit_once(':') {
+            Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok_and(|p| p > 0),
+            None => false,
+        };
+
+        if !valid {
+            return Err(format!(
+                "Invalid TiKV placement driver address {}, expected host:port",
+                endpoint
+            ));
+        }
+    }
+
+    Ok(endpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_directory(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "surrealist-storage-{}-{}",
+            name,
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&path);
+
+        path
+    }
+
+    #[test]
+    fn builds_uri_for_each_driver() {
+        let path = scratch_directory("drivers");
+        let storage = path.to_string_lossy();
+
+        assert_eq!(build_storage_uri("memory", "", false).unwrap(), "memory");
+        assert_eq!(
+            build_storage_uri("file", &storage, true).unwrap(),
+            format!("rocksdb://{}", storage)
+        );
+        assert_eq!(
+            build_storage_uri("surrealkv+versioned", &format!(" {} ", storage), false).unwrap(),
+            format!("surrealkv+versioned://{}", storage)
+        );
+        assert_eq!(
+            build_storage_uri("tikv", "pd1:2379, pd2:2379", false).unwrap(),
+            "tikv://pd1:2379,pd2:2379"
+        );
+        assert!(build_storage_uri("unknown", "", false).is_err());
+
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn requires_existing_directory_unless_created() {
+        let path = scratch_directory("missing");
+        let storage = path.to_string_lossy();
+
+        assert!(build_storage_uri("surrealkv", &storage, false).is_err());
+        assert!(build_storage_uri("surrealkv", "", true).is_err());
+        assert!(build_storage_uri("surrealkv", &storage, true).is_ok());
+        assert!(path.is_dir());
+
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn rejects_directory_of_other_engine() {
+        let path = scratch_directory("engine");
+
+        fs::create_dir_all(&path).unwrap();
+        File::create(path.join("CURRENT")).unwrap();
+        File::create(path.join("IDENTITY")).unwrap();
+
+        assert_eq!(detect_storage_engine(&path), Some(StorageEngine::RocksDb));
+        assert!(build_storage_uri("file", &path.to_string_lossy(), false).is_ok());
+        assert!(build_storage_uri("surrealkv", &path.to_string_lossy(), false).is_err());
+
+        let _ = fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn parses_placement_driver_endpoints() {
+        assert_eq!(
+            parse_pd_endpoints("tikv://127.0.0.1:2379,[::1]:2380").unwrap(),
+            vec!["127.0.0.1:2379", "[::1]:2380"]
+        );
+        assert_eq!(
+            parse_pd_endpoints("a:1 b:2\nc:3").unwrap(),
+            vec!["a:1", "b:2", "c:3"]
+        );
+        assert!(parse_pd_endpoints(" , ").is_err());
+        assert!(parse_pd_endpoints("localhost").is_err());
+        assert!(parse_pd_endpoints(":2379").is_err());
+        assert!(parse_pd_endpoints("localhost:0").is_err());
+        assert!(parse_pd_endpoints("localhost:99999").is_err());
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    net::TcpListener as StdTcpListener,
+    sync::Mutex,
+};
+
+use log::{info, warn};
+use opentelemetry_proto::tonic::{
+    collector::trace::v1::{
+        trace_service_server::{TraceService, TraceServiceServer},
+        ExportTraceServiceRequest, ExportTraceServiceResponse,
+    },
+    common::v1::{any_value::Value as AnyValue, KeyValue},
+    trace::v1::{self as proto, status::StatusCode},
+};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::net::TcpListener;
+use tonic::{
+    codec::CompressionEncoding,
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+const MAX_TRACES: usize = 500;
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+const STATEMENT_KEYS: [&str; 3] = ["db.statement", "query", "statement"];
+
+/// A span received from the serve process
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start_time: u64,
+    pub duration_ms: f64,
+    pub attributes: BTreeMap<String, Value>,
+    pub error: bool,
+    pub status_message: Option<String>,
+}
+
+/// A span together with the spans it encloses
+#[derive(Serialize)]
+pub struct TraceNode {
+    #[serde(flatten)]
+    pub span: Span,
+    pub children: Vec<TraceNode>,
+}
+
+/// An overview of a single trace
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceSummary {
+    pub trace_id: String,
+    pub name: String,
+    pub statement: Option<String>,
+    pub start_time: u64,
+    pub duration_ms: f64,
+    pub span_count: usize,
+    pub error: bool,
+}
+
+/// The spans of the most recent traces, grouped by trace id
+#[derive(Default)]
+pub struct TraceStore {
+    traces: HashMap<String, Vec<Span>>,
+    order: VecDeque<String>,
+}
+
+#[derive(Default)]
+pub struct TelemetryState {
+    pub port: Mutex<Option<u16>>,
+    pub traces: Mutex<TraceStore>,
+}
+
+impl TraceStore {
+    pub fn clear(&mut self) {
+        self.traces.clear();
+        self.order.clear();
+    }
+
+    fn insert(&mut self, spans: Vec<Span>) {
+        for span in spans {
+            if !self.traces.contains_key(&span.trace_id) {
+                if self.order.len() >= MAX_TRACES {
+                    if let Some(oldest) = self.order.pop_front() {
+                        self.traces.remove(&oldest);
+                    }
+                }
+
+                self.order.push_back(span.trace_id.clone());
+            }
+
+            self.traces
+                .entry(span.trace_id.clone())
+                .or_default()
+                .push(span);
+        }
+    }
+}
+
+///
+/// Start the OTLP collector if it is not yet running, returning the
+/// endpoint the serve process should export its traces to
+///
+pub fn ensure_collector(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<TelemetryState>();
+    let mut port = state.port.lock().unwrap();
+
+    if let Some(port) = *port {
+        return Ok(format!("http://127.0.0.1:{}", port));
+    }
+
+    let listener = StdTcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.set_nonblocking(true).map(|_| l))
+        .map_err(|e| format!("Failed to start trace collector: {}", e))?;
+
+    let bound = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let handle = app.clone();
+
+    info!("Collecting traces on port {}", bound);
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!("Trace collector stopped: {}", err);
+                return;
+            }
+        };
+
+        let service = TraceServiceServer::new(TraceCollector { app: handle })
+            .accept_compressed(CompressionEncoding::Gzip)
+            .max_decoding_message_size(MAX_MESSAGE_SIZE);
+
+        let result = Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await;
+
+        if let Err(err) = result {
+            warn!("Trace collector stopped: {}", err);
+        }
+    });
+
+    *port = Some(bound);
+
+    Ok(format!("http://127.0.0.1:{}", bound))
+}
+
+/// Receives the spans exported by the serve process
+struct TraceCollector {
+    app: AppHandle,
+}
+
+#[tonic::async_trait]
+impl TraceService for TraceCollector {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        let spans: Vec<Span> = request
+            .into_inner()
+            .resource_spans
+            .into_iter()
+            .flat_map(|resource| resource.scope_spans)
+            .flat_map(|scope| scope.spans)
+            .map(convert_span)
+            .collect();
+
+        if !spans.is_empty() {
+            let count = spans.len();
+
+            self.app
+                .state::<TelemetryState>()
+                .traces
+                .lock()
+                .unwrap()
+                .insert(spans);
+
+            self.app
+                .emit("telemetry:spans", count)
+                .expect("trace update should be delivered");
+        }
+
+        Ok(Response::new(ExportTraceServiceResponse::default()))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn convert_span(span: proto::Span) -> Span {
+    let status = span.status.unwrap_or_default();
+    let duration = span
+        .end_time_unix_nano
+        .saturating_sub(span.start_time_unix_nano);
+
+    Span {
+        trace_id: to_hex(&span.trace_id),
+        span_id: to_hex(&span.span_id),
+        parent_span_id: (!span.parent_span_id.is_empty()).then(|| to_hex(&span.parent_span_id)),
+        name: span.name,
+        start_time: span.start_time_unix_nano,
+        duration_ms: duration as f64 / 1_000_000.0,
+        attributes: span.attributes.into_iter().map(convert_attribute).collect(),
+        error: status.code == StatusCode::Error as i32,
+        status_message: (!status.message.is_empty()).then_some(status.message),
+    }
+}
+
+/// Convert a KeyValue, keeping only scalar values
+fn convert_attribute(attribute: KeyValue) -> (String, Value) {
+    let value = match attribute.value.and_then(|v| v.value) {
+        Some(AnyValue::StringValue(text)) => Value::from(text),
+        Some(AnyValue::BoolValue(flag)) => Value::from(flag),
+        Some(AnyValue::IntValue(int)) => Value::from(int),
+        Some(AnyValue::DoubleValue(double)) => Value::from(double),
+        _ => Value::Null,
+    };
+
+    (attribute.key, value)
+}
+
+fn summarize(trace_id: &str, spans: &[Span]) -> TraceSummary {
+    let root = spans
+        .iter()
+        .find(|s| s.parent_span_id.is_none())
+        .or_else(|| spans.iter().min_by_key(|s| s.start_time));
+
+    let start = spans.iter().map(|s| s.start_time).min().unwrap_or_default();
+    let end = spans
+        .iter()
+        .map(|s| s.start_time + (s.duration_ms * 1_000_000.0) as u64)
+        .max()
+        .unwrap_or_default();
+
+    let statement = spans.iter().find_map(|s| {
+        STATEMENT_KEYS
+            .iter()
+            .find_map(|k| s.attributes.get(*k)?.as_str().map(str::to_owned))
+    });
+
+    TraceSummary {
+        trace_id: trace_id.to_owned(),
+        name: root.map(|s| s.name.clone()).unwrap_or_default(),
+        statement,
+        start_time: start,
+        duration_ms: end.saturating_sub(start) as f64 / 1_000_000.0,
+        span_count: spans.len(),
+        error: spans.iter().any(|s| s.error),
+    }
+}
+
+fn build_node<'a>(span: &'a Span, spans: &'a [Span], visited: &mut HashSet<&'a str>) -> TraceNode {
+    TraceNode {
+        span: span.clone(),
+        children: build_tree(Some(&span.span_id), spans, visited),
+    }
+}
+
+fn build_tree<'a>(
+    parent: Option<&str>,
+    spans: &'a [Span],
+    visited: &mut HashSet<&'a str>,
+) -> Vec<TraceNode> {
+    let children: Vec<&Span> = spans
+        .iter()
+        .filter(|s| match parent {
+            Some(parent) => s.parent_span_id.as_deref() == Some(parent),
+            // Spans whose parent was not exported are treated as roots
+            None => s
+                .parent_span_id
+                .as_ref()
+                .is_none_or(|p| !spans.iter().any(|o| &o.span_id == p)),
+        })
+        .collect();
+
+    let mut nodes = Vec::new();
+
+    for span in children {
+        // Each span is placed once, so spans referencing each other as
+        // parents cannot recurse endlessly
+        if visited.insert(&span.span_id) {
+            nodes.push(build_node(span, spans, visited));
+        }
+    }
+
+    nodes.sort_by_key(|n| n.span.start_time);
+    nodes
+}
+
+#[tauri::command]
+pub fn list_traces(
+    state: State<TelemetryState>,
+    limit: Option<usize>,
+    min_duration_ms: Option<f64>,
+) -> Vec<TraceSummary> {
+    let store = state.traces.lock().unwrap();
+    let mut summaries: Vec<TraceSummary> = store
+        .traces
+        .iter()
+        .map(|(id, spans)| summarize(id, spans))
+        .filter(|s| s.duration_ms >= min_duration_ms.unwrap_or_default())
+        .collect();
+
+    summaries.sort_by_key(|s| Reverse(s.start_time));
+    summaries.truncate(limit.unwrap_or(100));
+    summaries
+}
+
+#[tauri::command]
+pub fn get_trace(state: State<TelemetryState>, trace_id: &str) -> Result<Vec<TraceNode>, String> {
+    let store = state.traces.lock().unwrap();
+    let spans = store.traces.get(trace_id).ok_or("Trace not found")?;
+
+    let mut visited = HashSet::new();
+    let mut nodes = build_tree(None, spans, &mut visited);
+
+    // Spans forming a parent cycle are unreachable from any root
+    for span in spans {
+        if visited.insert(&span.span_id) {
+            nodes.push(build_node(span, spans, &mut visited));
+        }
+    }
+
+    nodes.sort_by_key(|n| n.span.start_time);
+
+    Ok(nodes)
+}
+
+#[tauri::command]
+pub fn clear_traces(state: State<TelemetryState>) {
+    state.traces.lock().unwrap().clear();
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use log::info;
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use super::{
+    shell,
+    transfer::{connection_arguments, TransferOptions},
+    DatabaseState,
+};
+
+static NEXT_TERMINAL_ID: AtomicU32 = AtomicU32::new(1);
+
+/// A surreal CLI process running in a pseudo-terminal
+pub struct TerminalSession {
+    pub window: String,
+    master: Box<dyn MasterPty + Send>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+}
+
+/// The state holding open terminal sessions keyed by their id
+pub struct TerminalState(pub Mutex<HashMap<u32, TerminalSession>>);
+
+#[derive(Clone, Serialize)]
+struct TerminalOutput {
+    id: u32,
+    data: String,
+}
+
+#[derive(Clone, Serialize)]
+struct TerminalExit {
+    id: u32,
+    code: Option<u32>,
+}
+
+fn terminal_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Forward terminal output to the window, keeping incomplete UTF-8 sequences
+/// until the rest of the character arrives
+fn stream_output(window: Window, id: u32, mut reader: Box<dyn Read + Send>) {
+    thread::spawn(move || {
+        let mut buffer = [0u8; 8192];
+        let mut pending = Vec::new();
+
+        while let Ok(read) = reader.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+
+            pending.extend_from_slice(&buffer[..read]);
+
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                Err(_) => pending.len(),
+            };
+
+            let data = String::from_utf8_lossy(&pending[..valid]).into_owned();
+
+            pending.drain(..valid);
+
+            window
+                .emit_to(
+                    window.label(),
+                    "terminal:output",
+                    TerminalOutput { id, data },
+                )
+                .expect("terminal output should be delivered");
+        }
+    });
+}
+
+fn close_session(mut session: TerminalSession) {
+    let _ = session.killer.kill();
+}
+
+///
+/// Close all terminal sessions opened by the given window
+///
+pub fn release_window(app: &AppHandle, label: &str) {
+    let state = app.state::<TerminalState>();
+    let mut sessions = state.0.lock().unwrap();
+    let ids: Vec<u32> = sessions
+        .iter()
+        .filter(|(_, s)| s.window == label)
+        .map(|(id, _)| *id)
+        .collect();
+
+    for id in ids {
+        if let Some(session) = sessions.remove(&id) {
+            info!("Closing terminal {} of window {}", id, label);
+            close_session(session);
+        }
+    }
+}
+
+///
+/// Close all terminal sessions
+///
+pub fn release_all(app: &AppHandle) {
+    let sessions: Vec<_> = app
+        .state::<TerminalState>()
+        .0
+        .lock()
+        .unwrap()
+        .drain()
+        .collect();
+
+    for (_, session) in sessions {
+        close_session(session);
+    }
+}
+
+///
+/// Spawn a surreal subcommand in a pseudo-terminal, defaulting to the SQL
+/// REPL. When a connection is given, its credentials and selection are
+/// passed along, falling back to the local instance without an endpoint.
+///
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub fn open_terminal(
+    window: Window,
+    state: State<TerminalState>,
+    database: State<DatabaseState>,
+    executable: Option<String>,
+    subcommand: Option<Vec<String>>,
+    connection: Option<TransferOptions>,
+    cols: u16,
+    rows: u16,
+) -> Result<u32, String> {
+    let mut args = subcommand.unwrap_or_else(|| vec!["sql".to_owned(), "--pretty".to_owned()]);
+    let mut executable = executable.unwrap_or_default();
+    let mut env = Vec::new();
+
+    if let Some(connection) = &connection {
+        let resolved = connection_arguments(&database, connection)?;
+
+        if !resolved.executable.is_empty() {
+            executable = resolved.executable;
+        }
+
+        args.extend(resolved.args);
+        env = resolved.env;
+    }
+
+    if executable.is_empty() {
+        executable = "surreal".to_owned();
+    }
+
+    let quoted: Vec<String> = std::iter::once(executable.as_str())
+        .chain(args.iter().map(String::as_str))
+        .map(shell::quote_argument)
+        .collect();
+
+    let shell_cmd = shell::build_shell_command(quoted.iter().map(String::as_str).collect());
+    let mut command = CommandBuilder::new(&shell_cmd[0]);
+
+    command.args(&shell_cmd[1..]);
+    command.env("TERM", "xterm-256color");
+
+    for (key, value) in env {
+        command.env(key, value);
+    }
+
+    let pair = native_pty_system()
+        .openpty(terminal_size(cols, rows))
+        .map_err(|e| format!("Failed to open terminal: {}", e))?;
+
+    let mut child = pair
+        .slave
+        .spawn_command(command)
+        .map_err(|e| format!("Failed to launch surreal: {}", e))?;
+
+    // Release our handle on the slave so the reader ends when the process exits
+    drop(pair.slave);
+
+    let reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+    let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+    let id = NEXT_TERMINAL_ID.fetch_add(1, Ordering::SeqCst);
+
+    info!("Opened terminal {}", id);
+
+    state.0.lock().unwrap().insert(
+        id,
+        TerminalSession {
+            window: window.label().to_owned(),
+            master: pair.master,
+            writer: Arc::new(Mutex::new(writer)),
+            killer: child.clone_killer(),
+        },
+    );
+
+    stream_output(window.clone(), id, reader);
+
+    thread::spawn(move || {
+        let code = child.wait().ok().map(|s| s.exit_code());
+
+        window
+            .state::<TerminalState>()
+            .0
+            .lock()
+            .unwrap()
+            .remove(&id);
+
+        window
+            .emit_to(window.label(), "terminal:exit", TerminalExit { id, code })
+            .expect("terminal exit should be delivered");
+    });
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn write_terminal(state: State<TerminalState>, id: u32, data: &str) -> Result<(), String> {
+    // The write may block on a full terminal, so the sessions are not kept
+    // locked while it happens
+    let writer = state
+        .0
+        .lock()
+        .unwrap()
+        .get(&id)
+        .map(|session| session.writer.clone())
+        .ok_or("Terminal not found")?;
+
+    let mut writer = writer.lock().unwrap();
+
+    writer
+        .write_all(data.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write to terminal: {}", e))
+}
+
+#[tauri::command]
+pub fn resize_terminal(
+    state: State<TerminalState>,
+    id: u32,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    let sessions = state.0.lock().unwrap();
+    let session = sessions.get(&id).ok_or("Terminal not found")?;
+
+    session
+        .master
+        .resize(terminal_size(cols, rows))
+        .map_err(|e| format!("Failed to resize terminal: {}", e))
+}
+
+#[tauri::command]
+pub fn close_terminal(state: State<TerminalState>, id: u32) {
+    let session = state.0.lock().unwrap().remove(&id);
+
+    if let Some(session) = session {
+        info!("Closing terminal {}", id);
+        close_session(session);
+    }
+}
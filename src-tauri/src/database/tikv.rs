@@ -0,0 +1,356 @@
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::{client::wait_until, kill_surreal_process, shell};
+use crate::paths::get_tikv_playground_directory;
+
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+/// The user supplied binaries used to run the playground
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TikvOptions {
+    pub pd_executable: String,
+    pub tikv_executable: String,
+}
+
+/// A running process of the playground
+pub struct TikvComponent {
+    pub name: &'static str,
+    pub child: Child,
+    pub log: PathBuf,
+}
+
+/// A local PD and TiKV stack, components listed in start order
+pub struct TikvCluster {
+    pub pd_endpoint: String,
+    pub components: Vec<TikvComponent>,
+}
+
+/// The lifecycle of the playground
+pub enum TikvPlayground {
+    Starting,
+    Running(TikvCluster),
+}
+
+#[derive(Default)]
+pub struct TikvState(pub Mutex<Option<TikvPlayground>>);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TikvStatus {
+    pub pd_endpoint: String,
+    pub components: Vec<TikvComponentStatus>,
+}
+
+#[derive(Serialize)]
+pub struct TikvComponentStatus {
+    pub name: &'static str,
+    pub pid: u32,
+    pub log: String,
+}
+
+/// Spawn a component through the user's shell, writing its output to a log file
+fn spawn_component(executable: &str, args: &[String], log: &Path) -> Result<Child, String> {
+    let output = File::create(log).map_err(|e| format!("Failed to create log file: {}", e))?;
+    let errors = output.try_clone().map_err(|e| e.to_string())?;
+
+    let quoted: Vec<String> = std::iter::once(executable)
+        .chain(args.iter().map(String::as_str))
+        .map(shell::quote_argument)
+        .collect();
+
+    let shell_cmd = shell::build_shell_command(quoted.iter().map(String::as_str).collect());
+    let mut cmd_chain = Command::new(&shell_cmd[0]);
+
+    shell::configure_command(&mut cmd_chain);
+
+    cmd_chain
+        .args(&shell_cmd[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(output))
+        .stderr(Stdio::from(errors))
+        .spawn()
+        .map_err(|e| format!("Failed to launch {}: {}", executable, e))
+}
+
+/// Wait for a component to pass its health check while it keeps running
+fn wait_healthy<C: Fn(&str) -> bool>(component: &mut TikvComponent, url: &str, check: C) -> bool {
+    let child = RefCell::new(&mut component.child);
+    let is_running = || child.borrow_mut().try_wait().is_ok_and(|s| s.is_none());
+
+    wait_until(url, is_running, check)
+}
+
+/// Stop the given components in reverse start order
+fn stop_components(components: Vec<TikvComponent>) {
+    for mut component in components.into_iter().rev() {
+        info!("Stopping {}", component.name);
+
+        kill_surreal_process(component.child.id());
+
+        let _ = component.child.wait();
+    }
+}
+
+fn emit_status(app: &AppHandle, stage: &str) {
+    app.emit("tikv:status", stage)
+        .expect("playground status should be delivered");
+}
+
+fn pick_port() -> Result<u16, String> {
+    portpicker::pick_unused_port().ok_or_else(|| "No free port available".to_owned())
+}
+
+///
+/// Start PD followed by TiKV, waiting for each to become healthy, and
+/// return the PD endpoint the serve process should connect to. Only one
+/// start may be in progress at a time.
+///
+pub fn start_playground(app: &AppHandle, options: &TikvOptions) -> Result<String, String> {
+    let state = app.state::<TikvState>();
+
+    {
+        let mut playground = state.0.lock().unwrap();
+
+        match &*playground {
+            Some(TikvPlayground::Running(cluster)) => return Ok(cluster.pd_endpoint.clone()),
+            Some(TikvPlayground::Starting) => {
+                return Err("The TiKV playground is already starting".into())
+            }
+            None => *playground = Some(TikvPlayground::Starting),
+        }
+    }
+
+    let launched = launch_playground(app, options);
+    let mut playground = state.0.lock().unwrap();
+
+    // The playground may have been stopped while it was starting
+    let starting = matches!(*playground, Some(TikvPlayground::Starting));
+
+    match launched {
+        Ok(cluster) if starting => {
+            let endpoint = cluster.pd_endpoint.clone();
+
+            *playground = Some(TikvPlayground::Running(cluster));
+            drop(playground);
+            emit_status(app, "ready");
+
+            Ok(endpoint)
+        }
+        Ok(cluster) => {
+            drop(playground);
+            stop_components(cluster.components);
+
+            Err("The TiKV playground was stopped while starting".into())
+        }
+        Err(err) => {
+            if starting {
+                *playground = None;
+            }
+
+            Err(err)
+        }
+    }
+}
+
+fn launch_playground(app: &AppHandle, options: &TikvOptions) -> Result<TikvCluster, String> {
+    let root = get_tikv_playground_directory();
+    let pd_dir = root.join("pd");
+    let tikv_dir = root.join("tikv");
+
+    for dir in [&pd_dir, &tikv_dir] {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create playground directory: {}", e))?;
+    }
+
+    let pd_client = pick_port()?;
+    let pd_peer = pick_port()?;
+    let tikv_port = pick_port()?;
+    let tikv_status = pick_port()?;
+    let pd_endpoint = format!("127.0.0.1:{}", pd_client);
+
+    info!("Starting PD on port {}", pd_client);
+    emit_status(app, "pd");
+
+    let pd_log = root.join("pd.log");
+    let pd_child = spawn_component(
+        &options.pd_executable,
+        &[
+            "--name=pd".to_owned(),
+            format!("--data-dir={}", pd_dir.display()),
+            format!("--client-urls=http://{}", pd_endpoint),
+            format!("--peer-urls=http://127.0.0.1:{}", pd_peer),
+        ],
+        &pd_log,
+    )?;
+
+    let mut components = vec![TikvComponent {
+        name: "pd",
+        child: pd_child,
+        log: pd_log,
+    }];
+
+    let pd_healthy = wait_healthy(
+        &mut components[0],
+        &format!("http://{}/pd/api/v1/health", pd_endpoint),
+        |body| {
+            serde_json::from_str::<Vec<Value>>(body).is_ok_and(|members| {
+                !members.is_empty() && members.iter().all(|m| m["health"] == true)
+            })
+        },
+    );
+
+    if !pd_healthy {
+        stop_components(components);
+        return Err(format!(
+            "PD did not become healthy, see {} for details",
+            root.join("pd.log").display()
+        ));
+    }
+
+    info!("Starting TiKV on port {}", tikv_port);
+    emit_status(app, "tikv");
+
+    let tikv_log = root.join("tikv.log");
+    let tikv_child = spawn_component(
+        &options.tikv_executable,
+        &[
+            format!("--pd-endpoints={}", pd_endpoint),
+            format!("--addr=127.0.0.1:{}", tikv_port),
+            format!("--status-addr=127.0.0.1:{}", tikv_status),
+            format!("--data-dir={}", tikv_dir.display()),
+        ],
+        &tikv_log,
+    );
+
+    match tikv_child {
+        Ok(child) => components.push(TikvComponent {
+            name: "tikv",
+            child,
+            log: tikv_log,
+        }),
+        Err(err) => {
+            stop_components(components);
+            return Err(err);
+        }
+    }
+
+    // TiKV is only usable once it registered itself as an active store
+    let tikv_healthy = wait_healthy(
+        &mut components[1],
+        &format!("http://{}/pd/api/v1/stores", pd_endpoint),
+        |body| {
+            serde_json::from_str::<Value>(body).is_ok_and(|stores| {
+                stores["stores"]
+                    .as_array()
+                    .is_some_and(|s| s.iter().any(|s| s["store"]["state_name"] == "Up"))
+            })
+        },
+    );
+
+    if !tikv_healthy {
+        stop_components(components);
+        return Err(format!(
+            "TiKV did not become healthy, see {} for details",
+            root.join("tikv.log").display()
+        ));
+    }
+
+    Ok(TikvCluster {
+        pd_endpoint,
+        components,
+    })
+}
+
+///
+/// Stop the playground, if running, in reverse start order
+///
+pub fn stop_playground(app: &AppHandle) {
+    let playground = app.state::<TikvState>().0.lock().unwrap().take();
+
+    // A playground which is still starting tears itself down once launched
+    if let Some(TikvPlayground::Running(cluster)) = playground {
+        stop_components(cluster.components);
+        emit_status(app, "stopped");
+    }
+}
+
+///
+/// Start the playground and launch the serve process against it once it
+/// is healthy, tearing the playground down again if serving fails
+///
+pub fn serve_with_playground<F>(app: AppHandle, options: TikvOptions, serve: F)
+where
+    F: FnOnce(&str) -> Result<(), String> + Send + 'static,
+{
+    std::thread::spawn(move || match start_playground(&app, &options) {
+        Ok(endpoint) => {
+            if serve(&endpoint).is_err() {
+                stop_playground(&app);
+            }
+        }
+        Err(err) => {
+            error!("Failed to start TiKV playground: {}", err);
+
+            app.emit("database:error", err)
+                .expect("error result should be delivered");
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_tikv_status(state: State<TikvState>) -> Option<TikvStatus> {
+    let playground = state.0.lock().unwrap();
+    let Some(TikvPlayground::Running(cluster)) = &*playground else {
+        return None;
+    };
+
+    Some(TikvStatus {
+        pd_endpoint: cluster.pd_endpoint.clone(),
+        components: cluster
+            .components
+            .iter()
+            .map(|c| TikvComponentStatus {
+                name: c.name,
+                pid: c.child.id(),
+                log: c.log.to_string_lossy().into_owned(),
+            })
+            .collect(),
+    })
+}
+
+///
+/// Read the end of the captured log of a playground component
+///
+#[tauri::command]
+pub fn read_tikv_log(component: &str) -> Result<String, String> {
+    let name = match component {
+        "pd" => "pd.log",
+        "tikv" => "tikv.log",
+        _ => return Err("Unknown playground component".into()),
+    };
+
+    let mut file = File::open(get_tikv_playground_directory().join(name))
+        .map_err(|e| format!("Failed to open log: {}", e))?;
+
+    let length = file.metadata().map(|m| m.len()).unwrap_or_default();
+    let mut contents = Vec::new();
+
+    file.seek(SeekFrom::Start(length.saturating_sub(LOG_TAIL_BYTES)))
+        .and_then(|_| file.read_to_end(&mut contents))
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
@@ -0,0 +1,232 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use log::info;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, CidrSubnet, DnType, ExtendedKeyUsagePurpose,
+    GeneralSubtree, IsCa, KeyPair, KeyUsagePurpose, NameConstraints, SanType,
+};
+use tauri::{AppHandle, Window};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use time::OffsetDateTime;
+
+use crate::paths::get_certificates_directory;
+
+const AUTHORITY_NAME: &str = "Surrealist Local Development CA";
+const AUTHORITY_VALIDITY_DAYS: i64 = 3650;
+const SERVER_VALIDITY_DAYS: i64 = 365;
+const SERVER_RENEW_DAYS: u64 = 30;
+
+/// Files of the unconstrained authority created by earlier versions
+const LEGACY_AUTHORITY_FILES: [&str; 2] = ["ca.crt", "ca.key"];
+
+/// The certificate and key the serve process should use for HTTPS
+pub struct TlsFiles {
+    pub certificate: PathBuf,
+    pub key: PathBuf,
+}
+
+fn authority_path() -> PathBuf {
+    get_certificates_directory().join("authority.crt")
+}
+
+fn tls_error(err: impl std::fmt::Display) -> String {
+    format!("Failed to create certificate: {}", err)
+}
+
+/// Write a file, restricting access to the current user for private keys
+fn write_file(path: &Path, contents: &str, private: bool) -> Result<(), String> {
+    let mut options = OpenOptions::new();
+
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+
+    #[cfg(not(unix))]
+    let _ = private;
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The parameters of the local authority. Certificates only reference the
+/// name and key of their issuer, so rebuilding the authority from these
+/// parameters and the cached key signs certificates trusted by the same CA.
+/// The authority may only issue server certificates for the loopback names,
+/// so trusting it does not allow impersonating any other host.
+fn authority_params() -> Result<CertificateParams, String> {
+    let mut params = CertificateParams::new(Vec::<String>::new()).map_err(tls_error)?;
+    let now = OffsetDateTime::now_utc();
+
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.name_constraints = Some(NameConstraints {
+        permitted_subtrees: vec![
+            GeneralSubtree::DnsName("localhost".to_owned()),
+            GeneralSubtree::IpAddress(CidrSubnet::from_addr_prefix(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                32,
+            )),
+            GeneralSubtree::IpAddress(CidrSubnet::from_addr_prefix(
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+                128,
+            )),
+        ],
+        excluded_subtrees: Vec::new(),
+    });
+    params
+        .distinguished_name
+        .push(DnType::CommonName, AUTHORITY_NAME);
+    params.not_before = now;
+    params.not_after = now + time::Duration::days(AUTHORITY_VALIDITY_DAYS);
+    params.key_usages = vec![
+        KeyUsagePurpose::KeyCertSign,
+        KeyUsagePurpose::CrlSign,
+        KeyUsagePurpose::DigitalSignature,
+    ];
+
+    Ok(params)
+}
+
+/// Load the cached local authority, creating it when missing
+fn load_authority(directory: &Path) -> Result<(Certificate, KeyPair), String> {
+    let key_path = directory.join("authority.key");
+    let cert_path = authority_path();
+
+    if let (Ok(key), true) = (fs::read_to_string(&key_path), cert_path.exists()) {
+        let key = KeyPair::from_pem(&key).map_err(tls_error)?;
+        let issuer = authority_params()?.self_signed(&key).map_err(tls_error)?;
+
+        return Ok((issuer, key));
+    }
+
+    info!("Creating local certificate authority");
+
+    for name in LEGACY_AUTHORITY_FILES {
+        let _ = fs::remove_file(directory.join(name));
+    }
+
+    let key = KeyPair::generate().map_err(tls_error)?;
+    let certificate = authority_params()?.self_signed(&key).map_err(tls_error)?;
+
+    write_file(&key_path, &key.serialize_pem(), true)?;
+    write_file(&cert_path, &certificate.pem(), false)?;
+
+    Ok((certificate, key))
+}
+
+/// Returns whether the server certificate is missing or close to expiring
+fn needs_renewal(path: &Path) -> bool {
+    let renew_after =
+        Duration::from_secs((SERVER_VALIDITY_DAYS as u64 - SERVER_RENEW_DAYS) * 86400);
+
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age > renew_after)
+}
+
+///
+/// Ensure a server certificate for localhost, 127.0.0.1 and ::1 signed by
+/// the local authority exists in the data directory
+///
+pub fn ensure_certificates() -> Result<TlsFiles, String> {
+    let directory = get_certificates_directory();
+    let files = TlsFiles {
+        certificate: directory.join("server.crt"),
+        key: directory.join("server.key"),
+    };
+
+    if !needs_renewal(&files.certificate) && files.key.exists() && authority_path().exists() {
+        return Ok(files);
+    }
+
+    fs::create_dir_all(&directory)
+        .map_err(|e| format!("Failed to create certificates directory: {}", e))?;
+
+    let (authority, authority_key) = load_authority(&directory)?;
+
+    info!("Issuing local server certificate");
+
+    let mut params = CertificateParams::new(vec!["localhost".to_owned()]).map_err(tls_error)?;
+    let now = OffsetDateTime::now_utc();
+
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "localhost");
+    params.subject_alt_names.extend([
+        SanType::IpAddress(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        SanType::IpAddress(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+    ]);
+    params.not_before = now;
+    params.not_after = now + time::Duration::days(SERVER_VALIDITY_DAYS);
+    params.key_usages = vec![
+        KeyUsagePurpose::DigitalSignature,
+        KeyUsagePurpose::KeyEncipherment,
+    ];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+
+    let key = KeyPair::generate().map_err(tls_error)?;
+    let certificate = params
+        .signed_by(&key, &authority, &authority_key)
+        .map_err(tls_error)?;
+
+    write_file(&files.key, &key.serialize_pem(), true)?;
+    write_file(&files.certificate, &certificate.pem(), false)?;
+
+    Ok(files)
+}
+
+///
+/// Read the certificate of the local authority, if it was created
+///
+pub fn read_authority() -> Option<String> {
+    fs::read_to_string(authority_path()).ok()
+}
+
+#[tauri::command]
+pub fn get_tls_authority() -> Result<String, String> {
+    ensure_certificates()?;
+
+    read_authority().ok_or_else(|| "Local certificate authority is missing".to_owned())
+}
+
+#[tauri::command]
+pub async fn export_tls_authority(
+    app: AppHandle,
+    window: Window,
+) -> Result<Option<String>, String> {
+    ensure_certificates()?;
+
+    let mut dialog = app
+        .dialog()
+        .file()
+        .add_filter("Certificate", &["crt", "pem"])
+        .set_file_name("surrealist-ca.crt");
+
+    #[cfg(desktop)]
+    {
+        dialog = dialog.set_parent(&window);
+    }
+
+    let Some(FilePath::Path(path)) = dialog.blocking_save_file() else {
+        return Ok(None);
+    };
+
+    fs::copy(authority_path(), &path)
+        .map_err(|e| format!("Failed to export certificate: {}", e))?;
+
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
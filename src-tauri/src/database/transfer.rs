@@ -0,0 +1,318 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use super::{kill_surreal_process, spawn_surreal_command, DatabaseState};
+use crate::{config::vault::is_vault_reference, whitelist::append_allowed_file};
+
+static NEXT_TRANSFER_ID: AtomicU32 = AtomicU32::new(1);
+
+/// The state holding the process ids of running transfers
+pub struct TransferState(pub Mutex<HashMap<u32, u32>>);
+
+/// The database and credentials to export from or import into. When no
+/// endpoint is given, the running local serve instance is used.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOptions {
+    pub endpoint: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub token: Option<String>,
+    pub namespace: String,
+    pub database: String,
+    #[serde(default)]
+    pub executable: String,
+}
+
+#[derive(Clone, Serialize)]
+struct TransferOutput {
+    id: u32,
+    message: String,
+}
+
+#[derive(Clone, Serialize)]
+struct TransferProgress {
+    id: u32,
+    bytes: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct TransferResult {
+    id: u32,
+    success: bool,
+    cancelled: bool,
+    path: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TransferKind {
+    Export,
+    Import,
+}
+
+///
+/// The surreal CLI executable, arguments and environment connecting to a
+/// database. Passwords and tokens are passed through the environment so
+/// they do not show up in the process list.
+///
+pub struct CliConnection {
+    pub executable: String,
+    pub args: Vec<String>,
+    pub env: Vec<(&'static str, String)>,
+}
+
+/// Build the surreal CLI connection for the given options
+pub fn connection_arguments(
+    state: &DatabaseState,
+    options: &TransferOptions,
+) -> Result<CliConnection, String> {
+    let mut executable = options.executable.clone();
+    let mut args = Vec::new();
+    let mut env = Vec::new();
+
+    let secrets = [&options.password, &options.token];
+
+    if secrets.into_iter().flatten().any(|s| is_vault_reference(s)) {
+        return Err("Unlock the vault to transfer data".into());
+    }
+
+    match &options.endpoint {
+        Some(endpoint) => {
+            args.extend(["--conn".to_owned(), endpoint.clone()]);
+
+            if let Some(token) = options.token.as_ref().filter(|t| !t.is_empty()) {
+                env.push(("SURREAL_TOKEN", token.clone()));
+            } else if let Some(username) = &options.username {
+                args.extend(["--user".to_owned(), username.clone()]);
+                env.push(("SURREAL_PASS", options.password.clone().unwrap_or_default()));
+            }
+        }
+        None => {
+            let process = state.0.lock().unwrap();
+            let process = process
+                .as_ref()
+                .ok_or("The local database is not running")?;
+
+            args.extend(["--conn".to_owned(), process.endpoint()]);
+            args.extend(["--user".to_owned(), process.username.clone()]);
+            env.push(("SURREAL_PASS", process.password.clone()));
+
+            if executable.is_empty() {
+                executable = process.executable.clone();
+            }
+        }
+    }
+
+    args.extend(["--ns".to_owned(), options.namespace.clone()]);
+    args.extend(["--db".to_owned(), options.database.clone()]);
+
+    Ok(CliConnection {
+        executable,
+        args,
+        env,
+    })
+}
+
+/// A hidden file next to the export target which is renamed once complete
+fn partial_path(target: &Path) -> PathBuf {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+
+    target.with_file_name(format!(".{}.{}.partial", name, std::process::id()))
+}
+
+fn stream_output<R: Read + Send + 'static>(window: Window, id: u32, reader: R) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            window
+                .emit("transfer:output", TransferOutput { id, message: line })
+                .expect("transfer output should be delivered");
+        }
+    });
+}
+
+///
+/// Run a surreal export or import of the given file in the background.
+/// Exports are written to a partial file which only replaces the target
+/// once the export succeeds.
+///
+pub fn start_transfer(
+    window: Window,
+    kind: TransferKind,
+    connection: CliConnection,
+    path: PathBuf,
+) -> Result<u32, String> {
+    let id = NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed);
+    let (subcommand, file) = match kind {
+        TransferKind::Export => ("export", partial_path(&path)),
+        TransferKind::Import => ("import", path.clone()),
+    };
+
+    let args: Vec<String> = std::iter::once(subcommand.to_owned())
+        .chain(connection.args)
+        .chain([file.to_string_lossy().into_owned()])
+        .collect();
+
+    let mut child = spawn_surreal_command(&connection.executable, &args, &connection.env)
+        .map_err(|e| format!("Failed to launch surreal: {}", e))?;
+
+    let handle = window.app_handle().clone();
+
+    handle
+        .state::<TransferState>()
+        .0
+        .lock()
+        .unwrap()
+        .insert(id, child.id());
+
+    stream_output(window.clone(), id, child.stdout.take().unwrap());
+    stream_output(window.clone(), id, child.stderr.take().unwrap());
+
+    let running = Arc::new(AtomicBool::new(true));
+
+    if kind == TransferKind::Export {
+        let running = running.clone();
+        let window = window.clone();
+        let file = file.clone();
+
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                let bytes = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+
+                window
+                    .emit("transfer:progress", TransferProgress { id, bytes })
+                    .expect("transfer progress should be delivered");
+
+                thread::sleep(Duration::from_millis(500));
+            }
+        });
+    }
+
+    thread::spawn(move || {
+        let status = child.wait();
+
+        running.store(false, Ordering::Relaxed);
+
+        let state = handle.state::<TransferState>();
+        let cancelled = state.0.lock().unwrap().remove(&id).is_none();
+        let mut success = !cancelled && status.is_ok_and(|s| s.success());
+
+        if kind == TransferKind::Export {
+            if success {
+                success = fs::rename(&file, &path).is_ok();
+            }
+
+            if success {
+                append_allowed_file(&path);
+            } else {
+                // Only the incomplete export is removed, any existing file
+                // at the target is left untouched
+                let _ = fs::remove_file(&file);
+            }
+        }
+
+        let path = path.canonicalize().unwrap_or(path);
+
+        info!("Transfer {} finished (success: {})", id, success);
+
+        window
+            .emit(
+                "transfer:finish",
+                TransferResult {
+                    id,
+                    success,
+                    cancelled,
+                    path: path.to_string_lossy().into_owned(),
+                },
+            )
+            .expect("transfer result should be delivered");
+    });
+
+    Ok(id)
+}
+
+fn picked_path(file: Option<FilePath>) -> Option<PathBuf> {
+    match file? {
+        FilePath::Path(buf) => Some(buf),
+        FilePath::Url(_) => None,
+    }
+}
+
+#[tauri::command]
+pub async fn export_database(
+    app: AppHandle,
+    window: Window,
+    database: State<'_, DatabaseState>,
+    options: TransferOptions,
+) -> Result<Option<u32>, String> {
+    let connection = connection_arguments(&database, &options)?;
+    let mut dialog = app
+        .dialog()
+        .file()
+        .add_filter("SurrealQL", &["surql"])
+        .set_file_name(format!("{}-{}.surql", options.namespace, options.database));
+
+    #[cfg(desktop)]
+    {
+        dialog = dialog.set_parent(&window);
+    }
+
+    let Some(path) = picked_path(dialog.blocking_save_file()) else {
+        return Ok(None);
+    };
+
+    info!("Exporting database to {}", path.display());
+
+    start_transfer(window, TransferKind::Export, connection, path).map(Some)
+}
+
+#[tauri::command]
+pub async fn import_database(
+    app: AppHandle,
+    window: Window,
+    database: State<'_, DatabaseState>,
+    options: TransferOptions,
+) -> Result<Option<u32>, String> {
+    let connection = connection_arguments(&database, &options)?;
+    let mut dialog = app.dialog().file().add_filter("SurrealQL", &["surql"]);
+
+    #[cfg(desktop)]
+    {
+        dialog = dialog.set_parent(&window);
+    }
+
+    let Some(path) = picked_path(dialog.blocking_pick_file()) else {
+        return Ok(None);
+    };
+
+    info!("Importing database from {}", path.display());
+
+    start_transfer(window, TransferKind::Import, connection, path).map(Some)
+}
+
+#[tauri::command]
+pub fn cancel_transfer(state: State<TransferState>, id: u32) -> bool {
+    let pid = state.0.lock().unwrap().remove(&id);
+
+    match pid {
+        Some(pid) => {
+            kill_surreal_process(pid);
+            true
+        }
+        None => false,
+    }
+}
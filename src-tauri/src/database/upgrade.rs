@@ -0,0 +1,217 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+};
+
+use log::{error, info};
+use serde::Serialize;
+use tauri::{Emitter, Manager, State, Window};
+use time::OffsetDateTime;
+
+use super::{files, spawn_surreal_command, storage::build_storage_uri, DatabaseState};
+use crate::paths::get_upgrade_backups_directory;
+
+/// The storage directories currently being upgraded
+#[derive(Default)]
+pub struct UpgradeState(pub Mutex<Vec<PathBuf>>);
+
+impl UpgradeState {
+    /// Returns whether the given storage directory is being upgraded
+    pub fn is_upgrading(&self, path: &Path) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|p| files::same_path(p, path))
+    }
+
+    fn finish(&self, path: &Path) {
+        self.0.lock().unwrap().retain(|p| p != path);
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpgradeResult {
+    success: bool,
+    backup: String,
+}
+
+fn stream_output<R: Read + Send + 'static>(window: Window, reader: R) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            window
+                .emit("upgrade:output", line)
+                .expect("upgrade output should be delivered");
+        }
+    })
+}
+
+/// Resolve a backup path, making sure it lives in the upgrade backups directory
+fn resolve_backup(backup: &str) -> Result<PathBuf, String> {
+    let root = get_upgrade_backups_directory();
+    let path = Path::new(backup)
+        .canonicalize()
+        .map_err(|_| "Upgrade backup does not exist")?;
+
+    match root.canonicalize() {
+        Ok(root) if path.starts_with(&root) && path != root => Ok(path),
+        _ => Err("Invalid upgrade backup".into()),
+    }
+}
+
+///
+/// Upgrade a stopped local storage directory to the format of the given
+/// surreal binary, keeping a copy of the directory in case it must be
+/// rolled back
+///
+#[tauri::command]
+pub fn upgrade_local_storage(
+    window: Window,
+    state: State<DatabaseState>,
+    upgrades: State<UpgradeState>,
+    driver: &str,
+    storage: &str,
+    executable: &str,
+) -> Result<String, String> {
+    let path = PathBuf::from(storage.trim());
+
+    // The serve lock is held while claiming the directory, so the database
+    // cannot be started on it until the upgrade finished
+    {
+        let process = state.0.lock().unwrap();
+
+        if process.as_ref().is_some_and(|p| p.uses_storage(&path)) {
+            return Err("Stop the database before upgrading its storage".into());
+        }
+
+        if upgrades.is_upgrading(&path) {
+            return Err("This storage directory is already being upgraded".into());
+        }
+
+        upgrades.0.lock().unwrap().push(path.clone());
+    }
+
+    let result = start_upgrade(window, &path, driver, storage, executable);
+
+    if result.is_err() {
+        upgrades.finish(&path);
+    }
+
+    result
+}
+
+fn start_upgrade(
+    window: Window,
+    path: &Path,
+    driver: &str,
+    storage: &str,
+    executable: &str,
+) -> Result<String, String> {
+    let uri = build_storage_uri(driver, storage, false)?;
+
+    if uri == "memory" || uri.starts_with("tikv://") {
+        return Err("Only local storage directories can be upgraded".into());
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| files::slugify(&n.to_string_lossy()))
+        .unwrap_or_default();
+
+    let backup = get_upgrade_backups_directory().join(format!(
+        "{}-{}",
+        name,
+        OffsetDateTime::now_utc().unix_timestamp()
+    ));
+
+    info!("Copying {} before upgrading", path.display());
+
+    files::clone_directory(path, &backup)
+        .map_err(|e| format!("Failed to copy storage before upgrading: {}", e))?;
+
+    let mut child = spawn_surreal_command(executable, &["fix".to_owned(), uri], &[])
+        .map_err(|e| format!("Failed to launch surreal: {}", e))?;
+
+    let stdout = stream_output(window.clone(), child.stdout.take().unwrap());
+    let stderr = stream_output(window.clone(), child.stderr.take().unwrap());
+    let backup_path = backup.to_string_lossy().into_owned();
+    let result_path = backup_path.clone();
+    let path = path.to_owned();
+
+    thread::spawn(move || {
+        let success = child.wait().is_ok_and(|s| s.success());
+
+        let _ = stdout.join();
+        let _ = stderr.join();
+
+        window.state::<UpgradeState>().finish(&path);
+
+        if success {
+            info!("Upgraded storage directory");
+        } else {
+            error!("Failed to upgrade storage directory");
+        }
+
+        window
+            .emit(
+                "upgrade:finish",
+                UpgradeResult {
+                    success,
+                    backup: result_path,
+                },
+            )
+            .expect("upgrade result should be delivered");
+    });
+
+    Ok(backup_path)
+}
+
+///
+/// Restore a storage directory from the copy taken before it was upgraded
+///
+#[tauri::command]
+pub fn rollback_local_storage(
+    state: State<DatabaseState>,
+    upgrades: State<UpgradeState>,
+    storage: &str,
+    backup: &str,
+) -> Result<(), String> {
+    let path = PathBuf::from(storage.trim());
+    let backup = resolve_backup(backup)?;
+
+    // Claimed like an upgrade, so neither a serve nor an upgrade can start
+    // on the directory while it is being replaced
+    {
+        let process = state.0.lock().unwrap();
+
+        if process.as_ref().is_some_and(|p| p.uses_storage(&path)) {
+            return Err("Stop the database before rolling back its storage".into());
+        }
+
+        if upgrades.is_upgrading(&path) {
+            return Err("Wait for the upgrade to finish before rolling it back".into());
+        }
+
+        upgrades.0.lock().unwrap().push(path.clone());
+    }
+
+    info!("Rolling back upgrade of {}", path.display());
+
+    let result = files::replace_directory(&backup, &path)
+        .map_err(|e| format!("Failed to roll back storage: {}", e));
+
+    upgrades.finish(&path);
+
+    result
+}
+
+#[tauri::command]
+pub fn discard_upgrade_backup(backup: &str) -> Result<(), String> {
+    let backup = resolve_backup(backup)?;
+
+    fs::remove_dir_all(backup).map_err(|e| format!("Failed to delete upgrade backup: {}", e))
+}
@@ -83,16 +83,83 @@ fn main() {
                 .build(),
         )
         .manage(open::OpenResourceState(Default::default()))
+        .manage(config::sync::ConfigSyncState::default())
+        .manage(config::vault::VaultState::default())
         .manage(DatabaseState(Default::default()))
+        .manage(database::registry::RegistryState::default())
+        .manage(database::transfer::TransferState(Default::default()))
+        .manage(database::backup::BackupState::default())
+        .manage(database::monitor::MonitorState::default())
+        .manage(database::querylog::QueryLogState(Default::default()))
+        .manage(database::telemetry::TelemetryState::default())
+        .manage(database::tikv::TikvState::default())
+        .manage(database::terminal::TerminalState(Default::default()))
+        .manage(database::scratch::ScratchState(Default::default()))
+        .manage(database::upgrade::UpgradeState::default())
         .invoke_handler(tauri::generate_handler![
             analytics::track_event,
             config::load_config,
             config::save_config,
+            config::patch::patch_config,
+            config::stores::load_config_store,
+            config::stores::save_config_store,
+            config::stores::load_tab_content,
+            config::stores::save_tab_content,
+            config::vault::get_vault_status,
+            config::vault::create_vault,
+            config::vault::unlock_vault,
+            config::vault::lock_vault,
+            config::vault::change_vault_protection,
             config::backup_config,
             config::has_config_backup,
             config::restore_config_backup,
+            config::backups::list_config_backups,
+            config::backups::preview_config_backup,
+            config::backups::delete_config_backup,
+            config::migrations::migrate_config,
             database::start_database,
             database::stop_database,
+            database::storage::is_storage_missing,
+            database::registry::list_local_databases,
+            database::registry::create_local_database,
+            database::registry::rename_local_database,
+            database::registry::duplicate_local_database,
+            database::registry::delete_local_database,
+            database::branch::create_local_branch,
+            database::branch::restore_local_branch,
+            database::branch::delete_local_branch,
+            database::transfer::export_database,
+            database::transfer::import_database,
+            database::transfer::cancel_transfer,
+            database::backup::configure_backups,
+            database::backup::get_backup_settings,
+            database::backup::list_backups,
+            database::backup::create_backup,
+            database::backup::delete_backup,
+            database::backup::restore_backup,
+            database::monitor::configure_monitor,
+            database::monitor::get_database_stats,
+            database::querylog::configure_query_log,
+            database::querylog::list_slow_queries,
+            database::querylog::list_frequent_queries,
+            database::querylog::clear_query_log,
+            database::telemetry::list_traces,
+            database::telemetry::get_trace,
+            database::telemetry::clear_traces,
+            database::tikv::get_tikv_status,
+            database::tikv::read_tikv_log,
+            database::terminal::open_terminal,
+            database::terminal::write_terminal,
+            database::terminal::resize_terminal,
+            database::terminal::close_terminal,
+            database::tls::get_tls_authority,
+            database::tls::export_tls_authority,
+            database::discovery::discover_local_servers,
+            database::scratch::start_scratch_database,
+            database::scratch::stop_scratch_database,
+            database::upgrade::upgrade_local_storage,
+            database::upgrade::rollback_local_storage,
+            database::upgrade::discard_upgrade_backup,
             window::toggle_devtools,
             window::new_window,
             open::get_opened_resources,
@@ -112,6 +179,10 @@ fn main() {
                 open::store_resources(get_app_handle(), env::args());
             }
 
+            config::sync::start_config_watcher(app.handle().clone());
+            database::backup::start_backup_scheduler(app.handle().clone());
+            database::monitor::start_resource_monitor(app.handle().clone());
+
             tauri::async_runtime::block_on(window::open_new_window(app.handle()));
 
             Ok(())
@@ -135,9 +206,14 @@ fn main() {
             let state = app.state::<DatabaseState>();
             let process = state.0.lock().unwrap().take();
 
-            if let Some(child) = process {
-                database::kill_surreal_process(child.id())
+            if let Some(process) = process {
+                database::kill_surreal_process(process.child.id())
             }
+
+            database::scratch::release_all(app);
+            database::terminal::release_all(app);
+            database::tikv::stop_playground(app);
+            config::sync::flush_config(app);
         }
         _ => (),
     })
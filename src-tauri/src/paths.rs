@@ -25,10 +25,30 @@ pub fn get_config_path() -> PathBuf {
     config_path
 }
 
-/// The path to a backup configuration file
-pub fn get_config_backup_path(version: u32) -> PathBuf {
+/// The path to the encrypted connection secrets
+pub fn get_vault_path() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("vault.json");
+    config_path
+}
+
+/// The directory where the configuration stores are kept
+pub fn get_config_stores_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("stores");
+    config_path
+}
+
+/// The path to the configuration backups directory
+pub fn get_config_backups_directory() -> PathBuf {
     let mut config_path = get_data_directory();
     config_path.push("backups");
+    config_path
+}
+
+/// The path to a backup configuration file
+pub fn get_config_backup_path(version: u32) -> PathBuf {
+    let mut config_path = get_config_backups_directory();
     config_path.push(format!("config-version-{}.json", version));
     config_path
 }
@@ -40,6 +60,63 @@ pub fn get_file_whitelist_path() -> PathBuf {
     config_path
 }
 
+/// The path to the local database registry
+pub fn get_local_databases_path() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("local_databases.json");
+    config_path
+}
+
+/// The directory where managed local databases are stored
+pub fn get_local_databases_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("databases");
+    config_path
+}
+
+/// The directory where branches of a local database are stored
+pub fn get_local_branches_directory(id: &str) -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("branches");
+    config_path.push(id);
+    config_path
+}
+
+/// The directory where automatic local database backups are stored
+pub fn get_database_backups_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("database-backups");
+    config_path
+}
+
+/// The directory where storage copies are kept before upgrading them
+pub fn get_upgrade_backups_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("upgrade-backups");
+    config_path
+}
+
+/// The path to the local TiKV playground directory
+pub fn get_tikv_playground_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("tikv-playground");
+    config_path
+}
+
+/// The path to the local TLS certificates directory
+pub fn get_certificates_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("certificates");
+    config_path
+}
+
+/// The path to the pidfiles of running serve processes
+pub fn get_instances_directory() -> PathBuf {
+    let mut config_path = get_data_directory();
+    config_path.push("instances");
+    config_path
+}
+
 /// The path to the logs directory
 pub fn get_logs_directory() -> PathBuf {
     let mut config_path = get_data_directory();
@@ -46,12 +46,17 @@ pub async fn open_new_window(app: &AppHandle) {
 
     let window = builder.build().expect("Failed to create window");
 
-    window.on_window_event(move |event| {
-        if let WindowEvent::Focused(focused) = event {
-            if *focused {
-                set_last_focused_window(&window_label);
-            }
+    let handle = app.clone();
+
+    window.on_window_event(move |event| match event {
+        WindowEvent::Focused(true) => {
+            set_last_focused_window(&window_label);
+        }
+        WindowEvent::Destroyed => {
+            crate::database::scratch::release_window(&handle, &window_label);
+            crate::database::terminal::release_window(&handle, &window_label);
         }
+        _ => {}
     });
 }
 
@@ -81,8 +81,10 @@ export interface SurrealistAdapter {
 
 	/**
 	 * Start the database with the given parameters
+	 *
+	 * @param createStorage Whether to create a missing storage directory
 	 */
-	startDatabase(): Promise<void>;
+	startDatabase(createStorage?: boolean): Promise<void>;
 
 	/**
 	 * Stop the currently running database
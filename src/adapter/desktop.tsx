@@ -1,5 +1,5 @@
 import { getHotkeyHandler } from "@mantine/hooks";
-import { invoke } from "@tauri-apps/api/core";
+import { type InvokeArgs, invoke } from "@tauri-apps/api/core";
 import { Event, listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { open, save } from "@tauri-apps/plugin-dialog";
@@ -9,18 +9,31 @@ import { arch, type } from "@tauri-apps/plugin-os";
 import { open as openURL } from "@tauri-apps/plugin-shell";
 import { check } from "@tauri-apps/plugin-updater";
 import { compareVersions } from "compare-versions";
+import { klona } from "klona";
+import { isEqual } from "radash";
+import { openVaultUnlockModal, requestVaultUnlock } from "~/components/App/modals/vault-unlock";
 import { VIEW_PAGES } from "~/constants";
-import { ConfigStore, useConfigStore } from "~/stores/config";
+import { useConfigStore } from "~/stores/config";
 import { useDatabaseStore } from "~/stores/database";
 import { useInterfaceStore } from "~/stores/interface";
-import type { Platform, QueryTab, SurrealistConfig, ViewPage } from "~/types";
-import { getSetting, overwriteConfig, watchStore } from "~/util/config";
+import type {
+	ConfigError,
+	ConfigRecovery,
+	Platform,
+	QueryTab,
+	SurrealistConfig,
+	VaultStatus,
+	ViewPage,
+} from "~/types";
+import { getSetting, mergeConfigChanges, overwriteConfig, watchStore } from "~/util/config";
+import { CONFIG_VERSION } from "~/util/defaults";
 import { getConnection } from "~/util/connection";
 import { featureFlags } from "~/util/feature-flags";
 import { openAndReadFiles, openAndWriteFile } from "~/util/file-system";
 import { DeepLinkAuthEvent, NavigateViewEvent } from "~/util/global-events";
-import { showErrorNotification, showInfo } from "~/util/helpers";
+import { showErrorNotification, showInfo, showWarning } from "~/util/helpers";
 import { dispatchIntent, handleIntentRequest } from "~/util/intents";
+import { applyPatch, createPatch, type PatchOperation } from "~/util/json-patch";
 import { applyWindowScale } from "~/util/window-scale";
 import { adapter } from ".";
 import type { FileFilter, SurrealistAdapter } from "./base";
@@ -42,6 +55,54 @@ interface LinkResource {
 	params: string;
 }
 
+type MigrationReport =
+	| { outcome: "current"; version: number }
+	| { outcome: "upgraded" | "downgraded"; from: number; to: number; backup: string }
+	| { outcome: "refused"; version: number; reason: string };
+
+interface LoadedConfig {
+	config: string;
+	recovery: ConfigRecovery | null;
+	revision: number;
+}
+
+interface ConfigChanged {
+	revision: number;
+	config: string | null;
+	patch: PatchOperation[] | null;
+	source: string | null;
+}
+
+/**
+ * Inform the user their corrupted config file was replaced
+ */
+function showConfigRecovery(recovery: ConfigRecovery) {
+	const restored =
+		recovery.source === "default"
+			? "No valid backup was found, so your settings were reset."
+			: `Your settings were restored from ${recovery.path}.`;
+
+	showWarning({
+		title: "Your configuration file was corrupted",
+		subtitle: `${restored} The corrupted file was moved to ${recovery.quarantined}.`,
+		autoClose: false,
+	});
+}
+
+/**
+ * Invoke a config command, converting a failure into an Error
+ * describing what went wrong
+ */
+export async function invokeConfigCommand<T>(command: string, args?: InvokeArgs): Promise<T> {
+	try {
+		return await invoke<T>(command, args);
+	} catch (err) {
+		const { kind, message } = err as ConfigError;
+
+		throw new Error(message, { cause: kind });
+	}
+}
+
 /**
  * Surrealist adapter for running as Wails desktop app
  */
@@ -56,6 +117,8 @@ export class DesktopAdapter implements SurrealistAdapter {
 	public platform: Platform = "windows";
 
 	#startTask: any;
+	#configRevision = 0;
+	#configBase: SurrealistConfig | null = null;
 	#arch: string = arch();
 	#system: string = type();
 
@@ -78,8 +141,15 @@ export class DesktopAdapter implements SurrealistAdapter {
 			getHotkeyHandler([["mod+alt+i", () => invoke("toggle_devtools")]]),
 		);
 
-		getCurrentWindow().listen("config-updated", (event: Event<ConfigStore>) => {
-			overwriteConfig(event.payload);
+		getCurrentWindow().listen("config:changed", (event: Event<ConfigChanged>) => {
+			this.applyConfigChange(event.payload);
+		});
+
+		getCurrentWindow().listen("config:error", (event: Event<ConfigError>) => {
+			showErrorNotification({
+				title: "Failed to save configuration",
+				content: event.payload.message,
+			});
 		});
 
 		getCurrentWindow().listen("open-resource", () => {
@@ -149,7 +219,29 @@ export class DesktopAdapter implements SurrealistAdapter {
 			}
 		}
 
-		const config = await invoke<string>("load_config");
+		const migration = await invokeConfigCommand<MigrationReport>("migrate_config", {
+			targetVersion: CONFIG_VERSION,
+		});
+
+		if (migration.outcome === "upgraded") {
+			info(`Migrated config from version ${migration.from} to ${migration.to}`);
+		}
+
+		const { config, recovery, revision } =
+			await invokeConfigCommand<LoadedConfig>("load_config");
+
+		if (recovery) {
+			showConfigRecovery(recovery);
+		}
+
+		const vault = await this.getVaultStatus();
+
+		if (vault.locked && vault.protection === "password") {
+			setTimeout(openVaultUnlockModal, 250);
+		}
+
+		this.#configRevision = revision;
+		this.#configBase = JSON.parse(config);
 
 		return JSON.parse(config);
 	}
@@ -158,13 +250,100 @@ export class DesktopAdapter implements SurrealistAdapter {
 		return config;
 	}
 
-	public saveConfig(config: SurrealistConfig) {
-		return invoke<void>("save_config", {
-			config: JSON.stringify(config),
-		});
+	public async saveConfig(config: SurrealistConfig) {
+		const contents = JSON.parse(JSON.stringify(config));
+
+		try {
+			if (this.#configBase) {
+				const patch = createPatch(this.#configBase, contents);
+
+				if (patch.length === 0) {
+					return;
+				}
+
+				this.#configRevision = await invokeConfigCommand<number>("patch_config", {
+					patch,
+					revision: this.#configRevision,
+				});
+			} else {
+				this.#configRevision = await invokeConfigCommand<number>("save_config", {
+					config: JSON.stringify(contents),
+					revision: this.#configRevision,
+				});
+			}
+
+			this.#configBase = contents;
+		} catch (err: any) {
+			// Secrets cannot be saved until the vault is unlocked
+			if (err?.cause === "vaultLocked") {
+				requestVaultUnlock();
+			}
+
+			if (err?.cause !== "conflict") {
+				throw err;
+			}
+
+			// Another window or an external edit saved first
+			const { config: latest, revision } =
+				await invokeConfigCommand<LoadedConfig>("load_config");
+
+			const merged = mergeConfigChanges(this.#configBase, contents, JSON.parse(latest));
+
+			this.#configRevision = revision;
+			this.#configBase = JSON.parse(latest);
+
+			overwriteConfig(merged);
+
+			await this.saveConfig(merged);
+		}
+	}
+
+	/**
+	 * Apply a config saved by another window or edited outside of Surrealist,
+	 * keeping any local changes which were not saved yet
+	 */
+	private async applyConfigChange({ revision, config, patch, source }: ConfigChanged) {
+		if (source === getCurrentWindow().label || revision <= this.#configRevision) {
+			return;
+		}
+
+		let latest: SurrealistConfig;
+
+		if (config !== null) {
+			latest = JSON.parse(config);
+		} else if (patch && this.#configBase && revision === this.#configRevision + 1) {
+			latest = applyPatch(klona(this.#configBase), patch);
+		} else {
+			// Missed a revision or a store was saved on its own
+			const loaded = await invokeConfigCommand<LoadedConfig>("load_config");
+
+			latest = JSON.parse(loaded.config);
+			revision = loaded.revision;
+		}
+
+		const current = JSON.parse(JSON.stringify(useConfigStore.getState()));
+		const hasPending = !isEqual(current, this.#configBase);
+
+		this.#configRevision = revision;
+
+		if (hasPending) {
+			const merged = mergeConfigChanges(this.#configBase, current, latest);
+
+			this.#configBase = latest;
+			useConfigStore.setState(merged);
+		} else {
+			this.#configBase = latest;
+			overwriteConfig(latest);
+		}
+	}
+
+	public async isStorageMissing() {
+		const { driver, storage } = useConfigStore.getState().settings.serving;
+
+		return invoke<boolean>("is_storage_missing", { driver, storage });
 	}
 
-	public async startDatabase() {
+	public async startDatabase(createStorage = false) {
 		const { username, password, port, driver, storage, executable, logLevel } =
 			useConfigStore.getState().settings.serving;
 
@@ -179,6 +358,7 @@ export class DesktopAdapter implements SurrealistAdapter {
 			executable,
 			logLevel,
 			legacyCompat,
+			createStorage,
 		});
 	}
 
@@ -319,6 +499,26 @@ export class DesktopAdapter implements SurrealistAdapter {
 		return invoke<void>("open_in_explorer", { path: query.query });
 	}
 
+	public getVaultStatus() {
+		return invokeConfigCommand<VaultStatus>("get_vault_status");
+	}
+
+	public createVault(password?: string) {
+		return invokeConfigCommand<void>("create_vault", { password });
+	}
+
+	public unlockVault(password?: string) {
+		return invokeConfigCommand<void>("unlock_vault", { password });
+	}
+
+	public lockVault() {
+		return invokeConfigCommand<void>("lock_vault");
+	}
+
+	public changeVaultProtection(password?: string) {
+		return invokeConfigCommand<void>("change_vault_protection", { password });
+	}
+
 	public pruneQueryFiles() {
 		const { sandbox, connections } = useConfigStore.getState();
 		const paths = [sandbox, ...connections]
@@ -1,8 +1,10 @@
 import { Icon, iconConsole, iconPlay, iconStop } from "@surrealdb/ui";
 import { useEffect, useState } from "react";
 import { adapter } from "~/adapter";
+import { DesktopAdapter } from "~/adapter/desktop";
 import { useIntent } from "~/hooks/routing";
 import { useStable } from "~/hooks/stable";
+import { useConfirmation } from "~/providers/Confirmation";
 import { openConnection } from "~/screens/surrealist/pages/Connection/connection/connection";
 import { useDatabaseStore } from "~/stores/database";
 import { tagEvent } from "~/util/analytics";
@@ -20,6 +22,25 @@ export function DatabaseServing() {
 	const isServing = useDatabaseStore((s) => s.isServing);
 	const isPending = useDatabaseStore((s) => s.servePending);
 
+	const startServing = useStable((createStorage?: boolean) => {
+		prepareServe();
+
+		adapter
+			.startDatabase(createStorage)
+			.then(() => void tagEvent("database_serve"))
+			.catch(() => stopServing());
+
+		setHasStarted(true);
+	});
+
+	const confirmCreateStorage = useConfirmation({
+		title: "Create storage directory?",
+		message:
+			"The storage directory does not exist yet. Do you want to create it and start serving a new empty database?",
+		confirmText: "Create and serve",
+		onConfirm: () => startServing(true),
+	});
+
 	const handleToggle = useStable(async () => {
 		if (isPending) {
 			return;
@@ -29,16 +50,12 @@ export function DatabaseServing() {
 			adapter.stopDatabase();
 
 			cancelServe();
+			setHasStarted(true);
+		} else if (adapter instanceof DesktopAdapter && (await adapter.isStorageMissing())) {
+			confirmCreateStorage();
 		} else {
-			prepareServe();
-
-			adapter
-				.startDatabase()
-				.then(() => void tagEvent("database_serve"))
-				.catch(() => stopServing());
+			startServing();
 		}
-
-		setHasStarted(true);
 	});
 
 	const openConsole = useStable(() => {
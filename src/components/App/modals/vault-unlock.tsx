@@ -0,0 +1,112 @@
+import { Button, Group, PasswordInput, Stack, Text } from "@mantine/core";
+import { closeModal, openModal } from "@mantine/modals";
+import { Icon, iconAuth } from "@surrealdb/ui";
+import { useState } from "react";
+import { adapter } from "~/adapter";
+import { DesktopAdapter } from "~/adapter/desktop";
+import { Form } from "~/components/Form";
+import { Spacer } from "~/components/Spacer";
+import { useStable } from "~/hooks/stable";
+
+function VaultUnlockForm() {
+	const [password, setPassword] = useState("");
+	const [error, setError] = useState("");
+	const [loading, setLoading] = useState(false);
+
+	const unlock = useStable(async () => {
+		if (!(adapter instanceof DesktopAdapter)) {
+			return;
+		}
+
+		try {
+			setLoading(true);
+			await adapter.unlockVault(password);
+			closeModal("vault-unlock");
+		} catch (err: any) {
+			setError(err.message);
+		} finally {
+			setLoading(false);
+		}
+	});
+
+	return (
+		<Form onSubmit={unlock}>
+			<Stack gap="lg">
+				<Text>
+					Your connection passwords and tokens are stored in an encrypted vault. Enter
+					your master password to unlock them.
+				</Text>
+				<PasswordInput
+					label="Master password"
+					value={password}
+					error={error || undefined}
+					onChange={(e) => setPassword(e.target.value)}
+					autoFocus
+				/>
+				<Group>
+					<Button
+						color="obsidian"
+						variant="light"
+						onClick={() => closeModal("vault-unlock")}
+					>
+						Skip
+					</Button>
+					<Spacer />
+					<Button
+						type="submit"
+						variant="gradient"
+						loading={loading}
+						disabled={!password}
+					>
+						Unlock
+					</Button>
+				</Group>
+			</Stack>
+		</Form>
+	);
+}
+
+/**
+ * Unlock the vault, asking for the master password when it is not kept
+ * in the OS keyring
+ */
+export async function requestVaultUnlock() {
+	if (!(adapter instanceof DesktopAdapter)) {
+		return;
+	}
+
+	const vault = await adapter.getVaultStatus();
+
+	if (!vault.locked) {
+		return;
+	}
+
+	if (vault.protection === "password") {
+		openVaultUnlockModal();
+	} else {
+		await adapter.unlockVault();
+	}
+}
+
+export function openVaultUnlockModal() {
+	return openModal({
+		modalId: "vault-unlock",
+		closeOnClickOutside: false,
+		title: (
+			<Group>
+				<Icon
+					size="lg"
+					path={iconAuth}
+				/>
+				<Text
+					fw={700}
+					fz="xl"
+					c="bright"
+				>
+					Unlock vault
+				</Text>
+			</Group>
+		),
+		children: <VaultUnlockForm />,
+	});
+}
@@ -1,8 +1,21 @@
-import { Box, Button, Checkbox, Group, MultiSelect, ScrollArea, Stack, Text } from "@mantine/core";
-import { Icon, iconCheck, iconDownload, iconUpload } from "@surrealdb/ui";
+import {
+	Box,
+	Button,
+	Checkbox,
+	Group,
+	MultiSelect,
+	PasswordInput,
+	ScrollArea,
+	Stack,
+	Text,
+} from "@mantine/core";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { Icon, iconAuth, iconCheck, iconDownload, iconUpload } from "@surrealdb/ui";
 import { assign } from "radash";
 import { useMemo, useState } from "react";
 import { adapter } from "~/adapter";
+import { DesktopAdapter } from "~/adapter/desktop";
+import { requestVaultUnlock } from "~/components/App/modals/vault-unlock";
 import { JSON_FILTER } from "~/constants";
 import { useConnectionList } from "~/hooks/connection";
 import { useCheckbox } from "~/hooks/events";
@@ -12,6 +25,143 @@ import { backupConfig } from "~/util/config";
 import { showErrorNotification, showInfo } from "~/util/helpers";
 import { applyMigrations } from "~/util/migrator";
 
+function VaultSection({ adapter }: { adapter: DesktopAdapter }) {
+	const client = useQueryClient();
+	const [password, setPassword] = useState("");
+	const [loading, setLoading] = useState(false);
+
+	const { data: vault } = useQuery({
+		queryKey: ["vault-status"],
+		queryFn: () => adapter.getVaultStatus(),
+	});
+
+	const perform = useStable(async (title: string, action: () => Promise<void>) => {
+		try {
+			setLoading(true);
+			await action();
+			setPassword("");
+		} catch (err: any) {
+			showErrorNotification({ title, content: err });
+		} finally {
+			setLoading(false);
+			client.invalidateQueries({ queryKey: ["vault-status"] });
+		}
+	});
+
+	const createVault = useStable(() => {
+		perform("Failed to create vault", () => adapter.createVault(password || undefined));
+	});
+
+	const changeProtection = useStable(() => {
+		perform("Failed to change vault protection", () =>
+			adapter.changeVaultProtection(password || undefined),
+		);
+	});
+
+	const lockVault = useStable(() => {
+		perform("Failed to lock vault", () => adapter.lockVault());
+	});
+
+	const unlockVault = useStable(() => {
+		perform("Failed to unlock vault", requestVaultUnlock);
+	});
+
+	if (!vault) {
+		return null;
+	}
+
+	const passwordRequired = !vault.keyringAvailable && !password;
+	const protection =
+		vault.protection === "password" ? "your master password" : "the system keyring";
+
+	const description = vault.created
+		? `Your connection passwords and tokens are encrypted in a vault protected by ${protection}.`
+		: "Encrypt your connection passwords and tokens in a vault instead of plain text.";
+
+	return (
+		<Box>
+			<Text
+				mt="xl"
+				fw={600}
+				fz={20}
+				c="bright"
+			>
+				Credential vault
+			</Text>
+			<Stack
+				align="start"
+				mt="md"
+			>
+				<Text>
+					{description}
+					<br />
+					Leave the master password empty to keep the vault key in the system
+					keyring instead.
+				</Text>
+				{(!vault.created || !vault.locked) && (
+					<PasswordInput
+						w={300}
+						label="Master password"
+						placeholder={vault.keyringAvailable ? "Use the system keyring" : undefined}
+						value={password}
+						onChange={(e) => setPassword(e.target.value)}
+					/>
+				)}
+				<Group>
+					{!vault.created && (
+						<Button
+							px="xl"
+							size="xs"
+							variant="gradient"
+							loading={loading}
+							disabled={passwordRequired}
+							rightSection={<Icon path={iconAuth} />}
+							onClick={createVault}
+						>
+							Create vault
+						</Button>
+					)}
+					{vault.created && !vault.locked && (
+						<>
+							<Button
+								px="xl"
+								size="xs"
+								variant="gradient"
+								loading={loading}
+								disabled={passwordRequired}
+								onClick={changeProtection}
+							>
+								Change protection
+							</Button>
+							<Button
+								px="xl"
+								size="xs"
+								color="obsidian"
+								loading={loading}
+								onClick={lockVault}
+							>
+								Lock vault
+							</Button>
+						</>
+					)}
+					{vault.created && vault.locked && (
+						<Button
+							px="xl"
+							size="xs"
+							variant="gradient"
+							loading={loading}
+							rightSection={<Icon path={iconAuth} />}
+							onClick={unlockVault}
+						>
+							Unlock vault
+						</Button>
+					)}
+				</Group>
+			</Stack>
+		</Box>
+	);
+}
+
 export function ManageDataTab() {
 	const connections = useConnectionList();
 
@@ -185,6 +335,7 @@ export function ManageDataTab() {
 							</Group>
 						</Stack>
 					</Box>
+					{adapter instanceof DesktopAdapter && <VaultSection adapter={adapter} />}
 				</Stack>
 			</Box>
 		</ScrollArea>
@@ -10,6 +10,7 @@ const DRIVERS = [
 	{ label: "Memory", value: "memory" },
 	{ label: "RocksDB", value: "file" },
 	{ label: "SurrealKV", value: "surrealkv" },
+	{ label: "SurrealKV (versioned)", value: "surrealkv+versioned" },
 	{ label: "TiKV", value: "tikv" },
 ];
 
@@ -31,7 +32,8 @@ export function ServingTab() {
 	const [historySize, setHistorySize] = useSetting(CAT, "historySize");
 	const [port, setPort] = useSetting(CAT, "port");
 
-	const isFileDriver = driver === "file" || driver === "surrealkv";
+	const isFileDriver =
+		driver === "file" || driver === "surrealkv" || driver === "surrealkv+versioned";
 
 	const updatePort = useStable((value: string | number) => {
 		setPort(value as number);
@@ -136,11 +138,11 @@ export function ServingTab() {
 						onChange={setDriver as any}
 					/>
 
-					{(driver === "file" || driver === "tikv" || driver === "surrealkv") && (
+					{(isFileDriver || driver === "tikv") && (
 						<TextInput
 							w="unset"
 							label={isFileDriver ? "Storage path" : "Storage cluster address"}
-							placeholder={isFileDriver ? "/path/to/storage" : "address:port"}
+							placeholder={isFileDriver ? "/path/to/storage" : "address:port, address:port"}
 							value={storage}
 							spellCheck={false}
 							onChange={(e) => setStorage(e.target.value)}
@@ -1,13 +1,14 @@
 import { Alert, Group } from "@mantine/core";
 import { Icon, iconDownload } from "@surrealdb/ui";
-import { invoke } from "@tauri-apps/api/core";
 import { relaunch } from "@tauri-apps/plugin-process";
 import { useState } from "react";
 import { adapter } from "~/adapter";
+import { invokeConfigCommand } from "~/adapter/desktop";
 import { useConfirmation } from "~/providers/Confirmation";
 import { useConfigStore } from "~/stores/config";
 import { useInterfaceStore } from "~/stores/interface";
 import { tagEvent } from "~/util/analytics";
+import { showErrorNotification } from "~/util/helpers";
 import { useStable } from "./stable";
 
 type Phase = "idle" | "downloading" | "error";
@@ -54,10 +55,18 @@ export function useDesktopUpdater() {
 		if (isDangerous) {
 			const config = useConfigStore.getState();
 
-			await invoke("backup_config", {
-				config: JSON.stringify(config),
-				version: config.configVersion,
-			});
+			try {
+				await invokeConfigCommand("backup_config", {
+					config: JSON.stringify(config),
+					version: config.configVersion,
+				});
+			} catch (err) {
+				showErrorNotification({
+					title: "Failed to back up configuration",
+					content: err,
+				});
+				return;
+			}
 		}
 
 		setPhase("downloading");
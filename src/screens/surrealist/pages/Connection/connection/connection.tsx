@@ -10,6 +10,7 @@ import {
 	Uuid,
 } from "surrealdb";
 import { adapter } from "~/adapter";
+import { requestVaultUnlock } from "~/components/App/modals/vault-unlock";
 import { fetchAPI } from "~/cloud/api";
 import { LQ_SUPPORTED, MAX_HISTORY_QUERY_LENGTH, SANDBOX } from "~/constants";
 import { hasCompletedOnboarding } from "~/hooks/onboarding";
@@ -34,6 +35,7 @@ import {
 	getAuthNS,
 	getConnection,
 	getConnectionVariant,
+	hasLockedSecrets,
 } from "~/util/connection";
 import { appendQueriesToConnection, loadDatasetSampleQueries } from "~/util/datasets";
 import { surqlDurationToSeconds } from "~/util/duration";
@@ -91,6 +93,13 @@ export async function openConnection(options?: ConnectOptions) {
 		throw new Error("No connection available");
 	}
 
+	// The credentials are only references until the vault is unlocked
+	if (hasLockedSecrets(connection.authentication)) {
+		useDatabaseStore.getState().setCurrentState("disconnected");
+		requestVaultUnlock();
+		return;
+	}
+
 	// FIXME currently unused due to defaults
 	const _strictSandbox = getSetting("behavior", "strictSandbox");
 	const newState = options?.isRetry ? "retrying" : "connecting";
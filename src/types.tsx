@@ -17,6 +17,16 @@ export type BreadcrumbItem = {
 	content?: ReactNode;
 };
 export type ColorScheme = "light" | "dark";
+export type ConfigErrorKind =
+	| "invalidJson"
+	| "notFound"
+	| "permissionDenied"
+	| "diskFull"
+	| "conflict"
+	| "invalidPassword"
+	| "keyringUnavailable"
+	| "vaultLocked"
+	| "io";
 export type DatabaseListMode = "list" | "grid";
 export type DiagramAlgorithm = "default" | "aligned" | "spaced";
 export type DiagramDirection = "default" | "ltr" | "rtl";
@@ -25,7 +35,7 @@ export type DiagramLineStyle = "default" | "metro" | "straight" | "smooth";
 export type DiagramLinks = "default" | "hidden" | "visible";
 export type DiagramMode = "default" | "fields" | "summary" | "simple";
 export type DiagramHoverFocus = "default" | "none" | "neighbours" | "chain" | "recursive";
-export type DriverType = "file" | "surrealkv" | "memory" | "tikv";
+export type DriverType = "file" | "surrealkv" | "surrealkv+versioned" | "memory" | "tikv";
 export type InvoiceStatus = "succeeded" | "pending" | "failed";
 export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
 export type MetricsDuration = "hour" | "half" | "day" | "week" | "month";
@@ -174,6 +184,25 @@ export type Assign<T, O extends object> = Omit<T, keyof O> & O;
 export type AuthTarget = [AuthType, string];
 export type Flags<T extends string> = Partial<Record<T, boolean>>;
 
+export interface ConfigError {
+	kind: ConfigErrorKind;
+	message: string;
+}
+
+export interface VaultStatus {
+	created: boolean;
+	locked: boolean;
+	protection: "keyring" | "password" | null;
+	keyringAvailable: boolean;
+}
+
+export interface ConfigRecovery {
+	reason: string;
+	quarantined: string;
+	source: "previous" | "backup" | "default";
+	path: string | null;
+}
+
 export interface Authentication {
 	mode: AuthMode;
 	protocol: Protocol;
@@ -14,8 +14,8 @@ export interface AuthBroadcast {
  * Broadcast an Auth0 state transition to other desktop windows.
  *
  * The current window label is included as `source` so the listener can skip
- * its own broadcasts. No-op on non-desktop adapters. Mirrors the
- * `config-updated` pattern used in `src/util/config.tsx`.
+ * its own broadcasts. No-op on non-desktop adapters. Mirrors the source
+ * filtering of the `config:changed` event in `src/adapter/desktop.tsx`.
  */
 export async function broadcastAuthEvent(type: AuthBroadcastType): Promise<void> {
 	if (adapter.id !== "desktop") {
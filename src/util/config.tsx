@@ -1,6 +1,5 @@
-import { getCurrentWindow } from "@tauri-apps/api/window";
 import { klona } from "klona";
-import { assign, debounce, isEmpty, isEqual } from "radash";
+import { assign, debounce, isEmpty, isEqual, isObject } from "radash";
 import type { StoreApi, UseBoundStore } from "zustand";
 import { adapter } from "~/adapter";
 import { DesktopAdapter } from "~/adapter/desktop";
@@ -8,6 +7,7 @@ import { useConfigStore } from "~/stores/config";
 import type { SurrealistConfig } from "~/types";
 import { CONFIG_VERSION, createBaseConfig } from "./defaults";
 import { showDowngradeWarningModal } from "./downgrade";
+import { showErrorNotification } from "./helpers";
 import { applyMigrations } from "./migrator";
 
 export type Category = keyof SurrealistConfig["settings"];
@@ -79,7 +79,12 @@ export async function startConfigSync() {
 			delay: 250,
 		},
 		(state: SurrealistConfig) => {
-			adapter.saveConfig(state);
+			adapter.saveConfig(state).catch((err) => {
+				showErrorNotification({
+					title: "Failed to save configuration",
+					content: err,
+				});
+			});
 		},
 	);
 
@@ -87,19 +92,9 @@ export async function startConfigSync() {
 	useConfigStore.setState(config);
 
 	// Sync the config with the adapter
-	useConfigStore.subscribe(async (updated) => {
+	useConfigStore.subscribe((updated) => {
 		if (!skipConfigSave) {
 			scheduleSave(updated);
-
-			if (adapter instanceof DesktopAdapter) {
-				skipConfigSave = true;
-
-				await getCurrentWindow()
-					.emit("config-updated", updated)
-					.then(() => {
-						skipConfigSave = false;
-					});
-			}
 		}
 	});
 
@@ -123,6 +118,56 @@ export function overwriteConfig(config: SurrealistConfig) {
 	}
 }
 
+/**
+ * Merge the local changes made to a config on top of the latest version,
+ * using the version both were based on to tell which side changed. Objects
+ * are merged per key and arrays of items with an id per item.
+ *
+ * @param base The config both versions were based on
+ * @param local The locally modified config
+ * @param latest The most recently saved config
+ * @returns The merged config
+ */
+export function mergeConfigChanges(base: any, local: any, latest: any): any {
+	if (isEqual(local, base)) {
+		return latest;
+	}
+
+	if (isObject(local) && isObject(latest)) {
+		const merged: Record<string, unknown> = {};
+		const keys = new Set([...Object.keys(latest), ...Object.keys(local)]);
+
+		for (const key of keys) {
+			const value = mergeConfigChanges(base?.[key], local[key], latest[key]);
+
+			if (value !== undefined) {
+				merged[key] = value;
+			}
+		}
+
+		return merged;
+	}
+
+	if (isIdentifiedList(local) && isIdentifiedList(latest)) {
+		const baseItems = new Map((isIdentifiedList(base) ? base : []).map((i) => [i.id, i]));
+		const localItems = new Map(local.map((i) => [i.id, i]));
+		const latestItems = new Map(latest.map((i) => [i.id, i]));
+		const ids = new Set([...latestItems.keys(), ...localItems.keys()]);
+
+		return [...ids]
+			.map((id) =>
+				mergeConfigChanges(baseItems.get(id), localItems.get(id), latestItems.get(id)),
+			)
+			.filter((item) => item !== undefined);
+	}
+
+	return local;
+}
+
+function isIdentifiedList(value: unknown): value is { id: string }[] {
+	return Array.isArray(value) && value.every((item) => typeof item?.id === "string");
+}
+
 export interface ConfigBackupOptions {
 	stripSensitive: boolean;
 	connections: string[];
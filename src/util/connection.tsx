@@ -82,6 +82,14 @@ export function getAuthLevel(auth: Authentication): AuthLevel {
 	return "root";
 }
 
+/**
+ * Returns whether the authentication holds a password or token which is
+ * still locked in the vault
+ */
+export function hasLockedSecrets(auth: Authentication) {
+	return [auth.password, auth.token].some((value) => value?.startsWith("vault:"));
+}
+
 /**
  * Extract the database from the given authentication
  */
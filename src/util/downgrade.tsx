@@ -3,9 +3,10 @@ import { openModal } from "@mantine/modals";
 import { invoke } from "@tauri-apps/api/core";
 import { relaunch } from "@tauri-apps/plugin-process";
 import { adapter } from "~/adapter";
-import { DesktopAdapter } from "~/adapter/desktop";
+import { DesktopAdapter, invokeConfigCommand } from "~/adapter/desktop";
 import { PrimaryTitle } from "~/components/PrimaryTitle";
 import { CONFIG_VERSION } from "./defaults";
+import { showErrorNotification } from "./helpers";
 
 async function hasConfigBackup() {
 	if (!(adapter instanceof DesktopAdapter)) {
@@ -18,9 +19,17 @@ async function hasConfigBackup() {
 }
 
 async function restoreBackup() {
-	await invoke("restore_config_backup", {
-		version: CONFIG_VERSION,
-	});
+	try {
+		await invokeConfigCommand("restore_config_backup", {
+			version: CONFIG_VERSION,
+		});
+	} catch (err) {
+		showErrorNotification({
+			title: "Failed to restore backup",
+			content: err,
+		});
+		return;
+	}
 
 	await relaunch();
 }
@@ -0,0 +1,217 @@
+import { isEqual, isObject } from "radash";
+
+type BasicOperation =
+	| { op: "add"; path: string; value: unknown }
+	| { op: "remove"; path: string }
+	| { op: "replace"; path: string; value: unknown };
+
+export type PatchOperation =
+	| BasicOperation
+	| { op: "move"; from: string; path: string }
+	| { op: "copy"; from: string; path: string }
+	| { op: "test"; path: string; value: unknown };
+
+function escapeToken(token: string) {
+	return token.replaceAll("~", "~0").replaceAll("/", "~1");
+}
+
+function unescapeToken(token: string) {
+	return token.replaceAll("~1", "/").replaceAll("~0", "~");
+}
+
+/**
+ * Compute the operations turning one array into another, keeping the
+ * unchanged elements at the start and end of the array in place
+ */
+function createArrayPatch(prev: any[], next: any[], path: string): PatchOperation[] {
+	const shortest = Math.min(prev.length, next.length);
+	let start = 0;
+	let end = 0;
+
+	while (start < shortest && isEqual(prev[start], next[start])) {
+		start++;
+	}
+
+	while (
+		end < shortest - start &&
+		isEqual(prev[prev.length - 1 - end], next[next.length - 1 - end])
+	) {
+		end++;
+	}
+
+	const removed = prev.length - end - start;
+	const added = next.length - end - start;
+	const common = Math.min(removed, added);
+	const operations: PatchOperation[] = [];
+
+	for (let i = start; i < start + common; i++) {
+		operations.push(...createPatch(prev[i], next[i], `${path}/${i}`));
+	}
+
+	for (let i = start + removed - 1; i >= start + common; i--) {
+		operations.push({ op: "remove", path: `${path}/${i}` });
+	}
+
+	for (let i = start + common; i < start + added; i++) {
+		operations.push({ op: "add", path: `${path}/${i}`, value: next[i] });
+	}
+
+	return operations;
+}
+
+/**
+ * Compute a JSON Patch describing the changes between two documents.
+ * Elements added to or removed from arrays are patched individually.
+ *
+ * @param prev The previous document
+ * @param next The updated document
+ * @param path The pointer to the compared values
+ * @returns The list of patch operations
+ */
+export function createPatch(prev: any, next: any, path = ""): PatchOperation[] {
+	if (isEqual(prev, next)) {
+		return [];
+	}
+
+	if (isObject(prev) && isObject(next)) {
+		const operations: PatchOperation[] = [];
+
+		for (const key of Object.keys(prev)) {
+			if (!(key in next)) {
+				operations.push({ op: "remove", path: `${path}/${escapeToken(key)}` });
+			}
+		}
+
+		for (const [key, value] of Object.entries(next)) {
+			const pointer = `${path}/${escapeToken(key)}`;
+
+			if (key in prev) {
+				operations.push(...createPatch(prev[key], value, pointer));
+			} else {
+				operations.push({ op: "add", path: pointer, value });
+			}
+		}
+
+		return operations;
+	}
+
+	if (Array.isArray(prev) && Array.isArray(next)) {
+		return createArrayPatch(prev, next, path);
+	}
+
+	return [{ op: "replace", path, value: next }];
+}
+
+function parsePointer(pointer: string) {
+	return pointer.split("/").slice(1).map(unescapeToken);
+}
+
+function readPointer(document: any, pointer: string): any {
+	return parsePointer(pointer).reduce((node, token) => {
+		if (node === null || typeof node !== "object" || !(token in node)) {
+			throw new Error(`Path ${pointer} does not exist`);
+		}
+
+		return node[token];
+	}, document);
+}
+
+function applyOperation(root: any, operation: BasicOperation): any {
+	const tokens = parsePointer(operation.path);
+	const last = tokens.pop();
+
+	if (last === undefined) {
+		return operation.op === "remove" ? undefined : operation.value;
+	}
+
+	const parent = tokens.reduce((node, token) => node?.[token], root);
+
+	if (parent === undefined || parent === null) {
+		throw new Error(`Path ${operation.path} does not exist`);
+	}
+
+	if (Array.isArray(parent)) {
+		const index = last === "-" ? parent.length : Number(last);
+
+		if (operation.op === "add") {
+			parent.splice(index, 0, operation.value);
+		} else if (operation.op === "replace") {
+			parent[index] = operation.value;
+		} else {
+			parent.splice(index, 1);
+		}
+	} else if (operation.op === "remove") {
+		delete parent[last];
+	} else {
+		parent[last] = operation.value;
+	}
+
+	return root;
+}
+
+/**
+ * Returns whether the precondition of an operation holds, meaning a tested
+ * value matches and a moved or copied value exists
+ *
+ * @param document The document the operation is applied to
+ * @param operation The operation to check
+ */
+export function isApplicable(document: any, operation: PatchOperation): boolean {
+	try {
+		switch (operation.op) {
+			case "test": {
+				return isEqual(readPointer(document, operation.path), operation.value);
+			}
+			case "move":
+			case "copy": {
+				readPointer(document, operation.from);
+				return true;
+			}
+			default: {
+				return true;
+			}
+		}
+	} catch {
+		return false;
+	}
+}
+
+/**
+ * Apply a JSON Patch to a document in place
+ *
+ * @param document The document to patch
+ * @param patch The operations to apply
+ * @returns The patched document
+ */
+export function applyPatch(document: any, patch: PatchOperation[]): any {
+	let root = document;
+
+	for (const operation of patch) {
+		switch (operation.op) {
+			case "test": {
+				if (!isEqual(readPointer(root, operation.path), operation.value)) {
+					throw new Error(`Test of ${operation.path} failed`);
+				}
+				break;
+			}
+			case "copy": {
+				const value = structuredClone(readPointer(root, operation.from));
+
+				root = applyOperation(root, { op: "add", path: operation.path, value });
+				break;
+			}
+			case "move": {
+				const value = readPointer(root, operation.from);
+
+				root = applyOperation(root, { op: "remove", path: operation.from });
+				root = applyOperation(root, { op: "add", path: operation.path, value });
+				break;
+			}
+			default: {
+				root = applyOperation(root, operation);
+			}
+		}
+	}
+
+	return root;
+}
@@ -1,5 +1,22 @@
 import { isArray, isObject } from "radash";
+import { adapter } from "~/adapter";
+import migrations from "~/assets/data/config-migrations.json";
 import type { SurrealistConfig } from "~/types";
+import { applyPatch, isApplicable, type PatchOperation } from "./json-patch";
+
+/**
+ * A breaking change between two consecutive config versions, shared with
+ * the desktop backend. Each step is a list of patches applied to every
+ * connection. A patch is skipped when a tested value does not match or a
+ * moved or copied value does not exist, while other failures are logged.
+ */
+interface ConfigMigration {
+	from: number;
+	upgrade: PatchOperation[][];
+	downgrade?: PatchOperation[][];
+}
+
+const MIGRATIONS = migrations as unknown as ConfigMigration[];
 
 /**
  * Apply migrations to the config object
@@ -11,36 +28,13 @@ import type { SurrealistConfig } from "~/types";
  * - Additions to the config should have a default value in defaults, or computed here
  */
 export function applyMigrations(config: any): SurrealistConfig {
-	const version = config.configVersion ?? -1;
-
-	// 2.0.0 -> 3.0.0: Convert the old connection format to the new one
-
-	if (version === 1) {
-		applyToConnections(config, (con) => {
-			con.authentication = con.connection;
-			con.authentication.mode = con.connection.authMode;
-
-			con.lastNamespace = con.connection.namespace;
-			con.lastDatabase = con.connection.database;
-
-			con.graphqlQuery = "";
-			con.graphqlVariables = "";
-
-			if (con.connection.authMode === "root" || con.connection.authMode === "namespace") {
-				con.authentication.namespace = "";
-			}
-
-			if (con.connection.authMode === "root") {
-				con.authentication.namespace = "";
-				con.authentication.database = "";
-			}
-
-			con.authentication.accessFields = con.connection.scopeFields || [];
-			con.authentication.authMode = undefined;
-			con.connection = undefined;
-		});
+	// Breaking changes between config versions, e.g. 2.0.0 -> 3.0.0
 
-		config.configVersion++;
+	for (const migration of MIGRATIONS) {
+		if (config.configVersion === migration.from) {
+			applyToConnections(config, (con) => applyStep(con, migration.upgrade));
+			config.configVersion++;
+		}
 	}
 
 	// 3.0.0 -> 3.0.2: Define missing access fields array
@@ -89,6 +83,36 @@ export function applyMigrations(config: any): SurrealistConfig {
 	return config;
 }
 
+function applyStep(connection: any, step: PatchOperation[][]) {
+	for (const patch of step) {
+		let patched = structuredClone(connection);
+
+		try {
+			for (const operation of patch) {
+				if (!isApplicable(patched, operation)) {
+					patched = undefined;
+					break;
+				}
+
+				patched = applyPatch(patched, [operation]);
+			}
+		} catch (err: any) {
+			adapter.warn("Migrator", `Failed to apply ${JSON.stringify(patch)}: ${err.message}`);
+			continue;
+		}
+
+		if (patched === undefined) {
+			continue;
+		}
+
+		for (const key of Object.keys(connection)) {
+			delete connection[key];
+		}
+
+		Object.assign(connection, patched);
+	}
+}
+
 function applyToConnections(config: any, cb: (con: any) => void) {
 	if (config.connections && isArray(config.connections)) {
 		for (const con of config.connections) {